@@ -0,0 +1,84 @@
+//! A global allocator that keeps track of peak memory usage
+//! Only built with the `peak-memory` feature, as counting every allocation slows everything down
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Bytes currently allocated
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+/// The most bytes that have been allocated at once since the last reset
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+/// Wraps the system allocator, counting how many bytes are live
+pub struct CountingAlloc;
+
+/// Records that `size` more bytes are live, and bumps the peak if needed
+fn grow(size: usize) {
+    let now = CURRENT.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK.fetch_max(now, Ordering::Relaxed);
+}
+
+/// Records that `size` bytes have been freed
+fn shrink(size: usize) {
+    CURRENT.fetch_sub(size, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        shrink(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        // Only count the change in size, so moving a block doesn't briefly count it twice
+        if !new_ptr.is_null() {
+            let old_size = layout.size();
+            if new_size > old_size {
+                grow(new_size - old_size);
+            } else {
+                shrink(old_size - new_size);
+            }
+        }
+        new_ptr
+    }
+}
+
+/// Starts a new measurement; the next `peak()` only covers what happens after this call
+pub fn reset_peak() {
+    PEAK.store(CURRENT.load(Ordering::Relaxed), Ordering::Relaxed);
+}
+
+/// Returns the most bytes that were allocated at once since the last `reset_peak()`
+pub fn peak() -> usize {
+    PEAK.load(Ordering::Relaxed)
+}
+
+#[test]
+fn test_peak_covers_freed_allocations() {
+    // Other test threads allocate and free at the same time, so only check what holds whatever
+    // they do: the peak can't be lower than the live total we saw while holding the vector
+    reset_peak();
+    let big = vec![0u8; 1024 * 1024];
+    let live = CURRENT.load(Ordering::Relaxed);
+    drop(big);
+    // The vector is gone, but the peak still remembers it
+    assert!(live >= 1024 * 1024);
+    assert!(peak() >= live);
+}
@@ -0,0 +1,27 @@
+//! Plays pictures one after another for the days that can show their working as an animation
+//! On a terminal each frame replaces the last, with a pause between them (`--set delay=ms`,
+//! default 150); when the output is piped, the frames are just printed one after another
+
+use crate::options;
+use std::io::{stdout, IsTerminal, Write};
+use std::thread;
+use std::time::Duration;
+
+/// How long each frame stays up on a terminal, unless `--set delay` says otherwise
+const DEFAULT_DELAY_MS: u64 = 150;
+
+/// Shows one frame: `caption` on its own line, then the picture
+pub fn frame(caption: &str, picture: &str) {
+    let mut out = stdout();
+    if out.is_terminal() {
+        // Clear the screen and go back to the top left
+        print!("\x1b[2J\x1b[H{}\n{}", caption, picture);
+        out.flush().unwrap();
+        let delay = options::get("delay")
+            .and_then(|ms| ms.parse().ok())
+            .unwrap_or(DEFAULT_DELAY_MS);
+        thread::sleep(Duration::from_millis(delay));
+    } else {
+        print!("{}\n{}", caption, picture);
+    }
+}
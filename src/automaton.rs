@@ -0,0 +1,239 @@
+//! A one-dimensional cellular automaton: an endless row of cells, each alive or dead, where each
+//! cell's next state depends on itself and the cells either side of it (day 12)
+//! Rules are written the way the puzzle writes them, eg. `..#.# => #`; the neighbourhood can be
+//! any odd width, and a neighbourhood without a rule dies
+
+use crate::wide::Int;
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+/// Which neighbourhoods make a live cell
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rules {
+    /// How many cells each rule looks at (always odd)
+    width: usize,
+    /// Indexed by the neighbourhood read as binary (leftmost cell is the highest bit)
+    alive: Vec<bool>,
+}
+
+impl Rules {
+    /// Returns the index into `alive` for a neighbourhood
+    fn index(around: &[bool]) -> usize {
+        around
+            .iter()
+            .fold(0, |index, &cell| index << 1 | usize::from(cell))
+    }
+
+    /// Returns true if the middle cell of `around` will be alive next generation
+    fn next(&self, around: &[bool]) -> bool {
+        self.alive[Rules::index(around)]
+    }
+}
+
+/// Parses a row of cells, where '#' is alive
+fn parse_cells(text: &str) -> Vec<bool> {
+    text.trim().chars().map(|ch| ch == '#').collect()
+}
+
+impl FromStr for Rules {
+    type Err = String;
+
+    /// One rule per line, eg. `...## => #`
+    fn from_str(s: &str) -> Result<Rules, String> {
+        let mut rules: Option<Rules> = None;
+        for line in s.lines().filter(|line| !line.trim().is_empty()) {
+            let (around, result) = line
+                .split_once("=>")
+                .ok_or_else(|| format!("Unable to parse rule: {}", line))?;
+            let around = parse_cells(around);
+            let rules = rules.get_or_insert_with(|| Rules {
+                width: around.len(),
+                alive: vec![false; 1 << around.len()],
+            });
+            if around.len() != rules.width || rules.width.is_multiple_of(2) || rules.width > 15 {
+                return Err(format!(
+                    "Every rule should look at the same odd number of cells (up to 15): {}",
+                    line
+                ));
+            }
+            rules.alive[Rules::index(&around)] = result.trim() == "#";
+        }
+        let rules = rules.ok_or("There are no rules")?;
+        if rules.alive[0] {
+            return Err("Cells would come alive everywhere, forever".to_string());
+        }
+        Ok(rules)
+    }
+}
+
+/// An endless row of cells, stepped forward a generation at a time
+/// Only the stretch from the first live cell to the last one is stored
+#[derive(Clone, Debug, PartialEq)]
+pub struct Automaton1D {
+    /// The position of the first cell in `cells`
+    first: Int,
+    /// Starts and ends with a live cell (unless there are none)
+    cells: Vec<bool>,
+    rules: Rules,
+}
+
+impl Automaton1D {
+    /// Starts an automaton with the row `initial` (eg. `#..#.#`), whose first cell is at 0
+    pub fn new(initial: &str, rules: Rules) -> Automaton1D {
+        let mut automaton = Automaton1D {
+            first: 0,
+            cells: parse_cells(initial),
+            rules,
+        };
+        automaton.trim();
+        automaton
+    }
+
+    /// The position of the first live cell (or 0 if there aren't any)
+    pub fn first(&self) -> Int {
+        self.first
+    }
+
+    /// The cells from the first live one to the last, inclusive
+    pub fn cells(&self) -> &[bool] {
+        &self.cells
+    }
+
+    /// Returns `len` cells starting from `start`, including the dead ones either side of the
+    /// stored stretch
+    pub fn window(&self, start: Int, len: usize) -> Vec<bool> {
+        (0..len as Int)
+            .map(|i| {
+                usize::try_from(start + i - self.first)
+                    .ok()
+                    .and_then(|offset| self.cells.get(offset))
+                    .cloned()
+                    .unwrap_or(false)
+            })
+            .collect()
+    }
+
+    /// Drops the dead cells from each end
+    fn trim(&mut self) {
+        let (start, end) = match (
+            self.cells.iter().position(|&cell| cell),
+            self.cells.iter().rposition(|&cell| cell),
+        ) {
+            (Some(start), Some(end)) => (start, end + 1),
+            _ => {
+                // Every cell has died
+                self.cells.clear();
+                self.first = 0;
+                return;
+            }
+        };
+        self.cells.truncate(end);
+        self.cells.drain(..start);
+        self.first += start as Int;
+    }
+
+    /// Moves on to the next generation
+    pub fn step(&mut self) {
+        // Pad with dead cells so the rules can see every cell that could come alive: up to
+        // `reach` cells beyond each end
+        let reach = self.rules.width / 2;
+        let padding = vec![false; reach * 2];
+        let padded: Vec<bool> = padding
+            .iter()
+            .chain(&self.cells)
+            .chain(&padding)
+            .cloned()
+            .collect();
+        self.cells = padded
+            .windows(self.rules.width)
+            .map(|around| self.rules.next(around))
+            .collect();
+        self.first -= reach as Int;
+        self.trim();
+    }
+}
+
+#[test]
+fn test_parse_rules() {
+    let rules: Rules = "...## => #\n..#.. => .\n#.#.# => #\n".parse().unwrap();
+    assert_eq!(rules.width, 5);
+    assert!(rules.next(&parse_cells("...##")));
+    assert!(rules.next(&parse_cells("#.#.#")));
+    // Unlisted neighbourhoods die
+    assert!(!rules.next(&parse_cells("##...")));
+    assert!("..# => #\n...# => #".parse::<Rules>().is_err());
+    assert!(".... => #".parse::<Rules>().is_err());
+    assert!("..... => #".parse::<Rules>().is_err());
+    assert!("".parse::<Rules>().is_err());
+}
+
+#[test]
+fn test_rule_90() {
+    // Each cell becomes the XOR of its neighbours, which draws a Sierpinski triangle
+    let rules: Rules = "#.. => #\n..# => #\n##. => #\n.## => #".parse().unwrap();
+    let mut automaton = Automaton1D::new("#", rules);
+    let mut rows = vec![];
+    for _ in 0..4 {
+        let row: String = automaton
+            .window(-4, 9)
+            .iter()
+            .map(|&cell| if cell { '#' } else { '.' })
+            .collect();
+        rows.push(row);
+        automaton.step();
+    }
+    assert_eq!(rows, ["....#....", "...#.#...", "..#...#..", ".#.#.#.#."]);
+    assert_eq!(automaton.first(), -4);
+    assert_eq!(automaton.cells(), parse_cells("#.......#"));
+}
+
+#[test]
+fn test_matches_a_naive_automaton() {
+    // Random rules and starting rows, checked against a simple set of live positions that looks
+    // at every cell that could possibly change
+    use std::collections::BTreeSet;
+    let mut rng = crate::generate::Rng::new(12);
+    for _ in 0..200 {
+        let width = [1, 3, 5][rng.range(0, 2)];
+        let mut alive: Vec<bool> = (0..1 << width).map(|_| rng.range(0, 1) == 1).collect();
+        alive[0] = false;
+        let rules = Rules { width, alive };
+        let initial: String = (0..rng.range(0, 12))
+            .map(|_| if rng.range(0, 2) == 0 { '#' } else { '.' })
+            .collect();
+        let mut automaton = Automaton1D::new(&initial, rules.clone());
+        let mut live: BTreeSet<Int> = initial
+            .chars()
+            .enumerate()
+            .filter(|(_, ch)| *ch == '#')
+            .map(|(i, _)| i as Int)
+            .collect();
+        let reach = (width / 2) as Int;
+        for _ in 0..20 {
+            automaton.step();
+            live = match (live.iter().next(), live.iter().next_back()) {
+                (Some(&low), Some(&high)) => (low - reach..=high + reach)
+                    .filter(|&i| {
+                        let around: Vec<bool> =
+                            (i - reach..=i + reach).map(|j| live.contains(&j)).collect();
+                        rules.next(&around)
+                    })
+                    .collect(),
+                _ => BTreeSet::new(),
+            };
+            let first = live.iter().next().cloned().unwrap_or(0);
+            let len = live
+                .iter()
+                .next_back()
+                .map_or(0, |&last| (last - first + 1) as usize);
+            assert_eq!(automaton.first(), first, "{} with {:?}", initial, rules);
+            assert_eq!(automaton.cells(), &automaton.window(first, len)[..]);
+            assert_eq!(
+                automaton.window(first, len),
+                (first..first + len as Int)
+                    .map(|i| live.contains(&i))
+                    .collect::<Vec<bool>>()
+            );
+        }
+    }
+}
@@ -0,0 +1,336 @@
+//! Compares the structure of two puzzle inputs, to help work out why a solver gets one right and
+//! not the other (eg. a friend's input has negative numbers, longer lines, or duplicate entries)
+//! Also runs a day against a whole directory of inputs, to compare the answers and timings, or
+//! runs each of a day's strategies on the same input, to check they agree and see which is faster
+
+use crate::input::normalize;
+use std::env;
+use std::ffi::OsString;
+use std::fs::{read_dir, read_to_string};
+use std::path::PathBuf;
+use std::process::Command;
+
+/// A summary of the shape of an input
+#[derive(Debug, PartialEq)]
+struct Stats {
+    lines: usize,
+    distinct_lines: usize,
+    /// The shortest and longest line lengths
+    line_length: Option<(usize, usize)>,
+    /// The smallest and largest value of each number on a line, by position
+    /// eg. for day 3 ("#1 @ 3,2: 5x4") the columns are id, x, y, width and height, so the ranges
+    /// give the bounding box of the claims
+    columns: Vec<(i64, i64)>,
+}
+
+/// Returns every integer in a line, in order ("-12" counts as negative, "12-3" as 12 and 3)
+fn numbers(line: &str) -> Vec<i64> {
+    let mut numbers = Vec::new();
+    let mut current = String::new();
+    let mut previous = ' ';
+    for ch in line.chars().chain(Some(' ')) {
+        if ch.is_ascii_digit() {
+            current.push(ch);
+        } else {
+            if let Ok(number) = current.parse() {
+                numbers.push(number);
+            }
+            current.clear();
+            // A '-' straight after a digit is a separator, not a sign
+            if ch == '-' && !previous.is_ascii_digit() {
+                current.push(ch);
+            }
+        }
+        previous = ch;
+    }
+    numbers
+}
+
+impl Stats {
+    fn new(text: &str) -> Stats {
+        let lines: Vec<&str> = text.lines().collect();
+        let mut distinct = lines.clone();
+        distinct.sort_unstable();
+        distinct.dedup();
+        let lengths = lines.iter().map(|line| line.len());
+        let line_length = lengths.clone().min().zip(lengths.max());
+        let mut columns: Vec<(i64, i64)> = Vec::new();
+        for line in &lines {
+            for (i, number) in numbers(line).into_iter().enumerate() {
+                match columns.get_mut(i) {
+                    Some((low, high)) => {
+                        *low = (*low).min(number);
+                        *high = (*high).max(number);
+                    }
+                    None => columns.push((number, number)),
+                }
+            }
+        }
+        Stats {
+            lines: lines.len(),
+            distinct_lines: distinct.len(),
+            line_length,
+            columns,
+        }
+    }
+
+    /// Returns (label, value) rows describing these stats, for printing in a table
+    fn rows(&self) -> Vec<(String, String)> {
+        let range = |(low, high): (i64, i64)| format!("{}..={}", low, high);
+        let mut rows = vec![
+            ("lines".to_string(), self.lines.to_string()),
+            (
+                "duplicate lines".to_string(),
+                (self.lines - self.distinct_lines).to_string(),
+            ),
+            (
+                "line length".to_string(),
+                self.line_length.map_or("-".to_string(), |(low, high)| {
+                    range((low as i64, high as i64))
+                }),
+            ),
+        ];
+        for (i, column) in self.columns.iter().enumerate() {
+            rows.push((format!("number {}", i + 1), range(*column)));
+        }
+        rows
+    }
+}
+
+/// Prints a table comparing the inputs in two files, marking the rows that differ with a '*'
+pub fn compare_inputs(a: &str, b: &str) -> Result<(), String> {
+    let read = |path: &str| {
+        read_to_string(path)
+            .map(|text| Stats::new(&normalize(&text)))
+            .map_err(|err| format!("Unable to read {}: {}", path, err))
+    };
+    let (a_rows, b_rows) = (read(a)?.rows(), read(b)?.rows());
+    // One input may have more numbers per line than the other, so take the longer list of labels
+    let labels = if a_rows.len() >= b_rows.len() {
+        &a_rows
+    } else {
+        &b_rows
+    };
+    let value = |rows: &[(String, String)], i: usize| {
+        rows.get(i)
+            .map_or("-".to_string(), |(_, value)| value.clone())
+    };
+    println!("{:<16} {:<24} {}", "", a, b);
+    for (i, (label, _)) in labels.iter().enumerate() {
+        let (a_value, b_value) = (value(&a_rows, i), value(&b_rows, i));
+        let marker = if a_value != b_value { " *" } else { "" };
+        let row = format!("{:<16} {:<24} {:<24}{}", label, a_value, b_value, marker);
+        println!("{}", row.trim_end());
+    }
+    Ok(())
+}
+
+/// What one part printed when it was run: its answer, and how long the solve stage took
+#[derive(Debug, PartialEq)]
+struct PartRun {
+    answer: String,
+    solve_time: String,
+}
+
+/// Picks the answers and solve times out of what a run with --timings printed
+/// Each part prints its answer, then an indented line of stage timings. A few parts print more
+/// than one line: the last one holds the answer, unless the answer is a picture (day 10's message)
+/// printed below its heading, in which case only the size of the picture is kept
+fn parse_run(stdout: &str) -> Vec<PartRun> {
+    let mut runs = Vec::new();
+    let mut lines: Vec<&str> = Vec::new();
+    for line in stdout.lines() {
+        if !line.starts_with(' ') {
+            lines.push(line);
+            continue;
+        }
+        let stages = match line.trim().strip_prefix('(') {
+            Some(stages) if stages.contains("output:") => stages.trim_end_matches(')'),
+            _ => continue,
+        };
+        let answer = match lines.iter().rposition(|line| line.contains("):")) {
+            Some(heading) if heading + 1 == lines.len() => {
+                let (_, answer) = lines[heading].split_once("):").unwrap();
+                answer.trim().to_string()
+            }
+            Some(heading) => format!("({} line picture)", lines.len() - heading - 1),
+            None => "?".to_string(),
+        };
+        let solve_time = stages
+            .split(", ")
+            .find_map(|stage| stage.strip_prefix("solve: "))
+            .unwrap_or("-");
+        runs.push(PartRun {
+            answer,
+            solve_time: solve_time.to_string(),
+        });
+        lines.clear();
+    }
+    runs
+}
+
+/// Runs a fresh copy of this program with `args` plus --timings, and picks out each part's
+/// answer and solve time
+/// Running a copy means a panic only spoils that one run: the error is the reason it failed
+fn run_copy(args: &[OsString]) -> Result<Vec<PartRun>, String> {
+    let program = env::current_exe().map_err(|err| format!("Unable to find ourself: {}", err))?;
+    let output = Command::new(&program)
+        .args(args)
+        .arg("--timings")
+        .env("RUST_BACKTRACE", "0")
+        .output()
+        .map_err(|err| format!("Unable to run {}: {}", program.display(), err))?;
+    if !output.status.success() {
+        // Show why it failed: a panic's message is on the line after "thread 'main' panicked
+        // at <where>", and anything else (eg. a missing input) is reported on its own
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr
+            .lines()
+            .skip_while(|line| !line.contains("panicked at"))
+            .nth(1)
+            .or_else(|| stderr.lines().find(|line| !line.trim().is_empty()))
+            .unwrap_or("no output");
+        return Err(reason.to_string());
+    }
+    Ok(parse_run(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Prints the header of a table of answers and solve times, one row per run
+fn print_header(first: &str) {
+    println!(
+        "{:<24} {:<24} {:<12} {:<24} Time",
+        first, "Part 1", "Time", "Part 2"
+    );
+}
+
+/// Prints one row of the table: `runs` from the run called `name`, or why it failed
+fn print_row(name: &str, runs: &Result<Vec<PartRun>, String>) {
+    match runs {
+        Ok(runs) => {
+            let row: String = runs
+                .iter()
+                .map(|run| format!(" {:<24} {:<12}", run.answer, run.solve_time))
+                .collect();
+            println!("{:<24}{}", name, row.trim_end());
+        }
+        Err(reason) => println!("{:<24} failed: {}", name, reason),
+    }
+}
+
+/// Runs `day` against every file in `dir`, and prints a table of the answers and solve times
+pub fn compare_answers(day: usize, dir: &str) -> Result<(), String> {
+    let mut paths: Vec<PathBuf> = read_dir(dir)
+        .map_err(|err| format!("Unable to read {}: {}", dir, err))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        // Skip hidden files, like editor swap files
+        .filter(|path| !path.file_name().unwrap().to_string_lossy().starts_with('.'))
+        .collect();
+    if paths.is_empty() {
+        return Err(format!("No input files in {}", dir));
+    }
+    paths.sort();
+    print_header("Input");
+    for path in paths {
+        let name = path.file_name().unwrap().to_string_lossy().into_owned();
+        let args = [
+            "--day".into(),
+            day.to_string().into(),
+            "--input".into(),
+            path.into(),
+        ];
+        print_row(&name, &run_copy(&args));
+    }
+    Ok(())
+}
+
+/// Runs `day` once for each of `values` of the `setting` that picks its strategy, all on the same
+/// input (`input` is passed on, eg. ["--input", "x.txt"]), and prints a table of the answers and
+/// solve times
+/// Returns an error if any run fails, or the strategies don't all give the same answers
+pub fn compare_strategies(
+    day: usize,
+    setting: &str,
+    values: &[&str],
+    input: &[String],
+) -> Result<(), String> {
+    print_header(setting);
+    let mut answers: Vec<Vec<String>> = Vec::new();
+    let mut failed = 0;
+    for value in values {
+        let mut args: Vec<OsString> = vec!["--day".into(), day.to_string().into()];
+        args.extend(input.iter().map(OsString::from));
+        args.push("--set".into());
+        args.push(format!("{}={}", setting, value).into());
+        let runs = run_copy(&args);
+        print_row(value, &runs);
+        match runs {
+            Ok(runs) => answers.push(runs.into_iter().map(|run| run.answer).collect()),
+            Err(_) => failed += 1,
+        }
+    }
+    if failed > 0 {
+        return Err(format!("{} of the strategies failed", failed));
+    }
+    if answers.windows(2).any(|pair| pair[0] != pair[1]) {
+        return Err(format!(
+            "The {} strategies disagree on the answers",
+            setting
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_numbers() {
+    assert_eq!(numbers("#123 @ 3,2: 5x4"), vec![123, 3, 2, 5, 4]);
+    assert_eq!(numbers("-6"), vec![-6]);
+    assert_eq!(numbers("+6"), vec![6]);
+    assert_eq!(
+        numbers("[1518-11-01 00:05] falls asleep"),
+        vec![1518, 11, 1, 0, 5]
+    );
+    assert_eq!(numbers("12-3"), vec![12, 3]);
+    assert_eq!(numbers("abcde"), Vec::<i64>::new());
+}
+
+#[test]
+fn test_stats() {
+    let stats = Stats::new("#1 @ 1,3: 4x4\n#2 @ 3,1: 4x4\n#3 @ 5,5: 2x2\n#3 @ 5,5: 2x2\n");
+    assert_eq!(
+        stats,
+        Stats {
+            lines: 4,
+            distinct_lines: 3,
+            line_length: Some((13, 13)),
+            columns: vec![(1, 3), (1, 5), (1, 5), (2, 4), (2, 4)],
+        }
+    );
+}
+
+#[test]
+fn test_parse_run() {
+    let stdout = concat!(
+        "Day2 (Part 1): Groups of 2s: 3 Groups of 3s: 4\n",
+        "Day2 (Part 1): Answer: 12\n",
+        "    (read: 1.00µs, solve: 2.50ms, output: 3.00µs)\n",
+        "Day 10 (Part 1):\n",
+        "#...#\n",
+        "#####\n",
+        "    (read: 1.00µs, parse: 2.00µs, output: 3.00µs)\n",
+        "    (peak memory: 12 KiB)\n",
+    );
+    assert_eq!(
+        parse_run(stdout),
+        vec![
+            PartRun {
+                answer: "Answer: 12".to_string(),
+                solve_time: "2.50ms".to_string()
+            },
+            PartRun {
+                answer: "(2 line picture)".to_string(),
+                solve_time: "-".to_string()
+            },
+        ]
+    );
+}
@@ -1,45 +1,61 @@
+use crate::input::{read_input, InputError};
+use crate::stopwatch;
+use crate::wide::{self, Int, Overflow};
 use std::collections::BTreeSet;
-use std::fs::read_to_string;
+
+/// The examples from the puzzle text
+pub const EXAMPLES: &[&str] = &[
+    include_str!("../data/examples/day1-1.txt"),
+    include_str!("../data/examples/day1-2.txt"),
+    include_str!("../data/examples/day1-3.txt"),
+    include_str!("../data/examples/day1-4.txt"),
+    include_str!("../data/examples/day1-5.txt"),
+];
 
 /// Takes a series of radio tuning adjustments, and prints the final frequency
-pub fn part1() {
-    let data = read_to_string("data/day1.txt").unwrap();
-    let answer: isize = data
+pub fn part1() -> Result<(), InputError> {
+    let data = read_input(1)?;
+    let answer = data
         .lines()
-        .map(|line| line.parse::<isize>().unwrap())
-        .sum();
+        .map(|line| line.parse::<Int>().unwrap())
+        .try_fold(0, |total, adjustment| wide::add(1, total, adjustment))?;
+    stopwatch::lap("solve");
     println!("Day 1 (Part 1): {}", answer);
+    Ok(())
 }
 
-pub fn part2() {
-    let data = read_to_string("data/day1.txt").unwrap();
-    let answer = do_part2(data);
+pub fn part2() -> Result<(), InputError> {
+    let data = read_input(1)?;
+    let answer = do_part2(data)?;
+    stopwatch::lap("solve");
     println!("Day 1 (Part 2): {}", answer);
+    Ok(())
 }
 
 /// Takes a list of \n separated frequency adjustments and starting at 0 finds the current
 /// frequency by accumulating the adjustments. Once it hits the same frequency twice, it returns
 /// that frequency
-fn do_part2(data: String) -> isize {
-    let mut frequencies: BTreeSet<isize> = BTreeSet::new();
+fn do_part2(data: String) -> Result<Int, Overflow> {
+    let mut frequencies: BTreeSet<Int> = BTreeSet::new();
     frequencies.insert(0);
     // This will be set to true once we're done
     let answer = data
         // Split into lines
         .lines()
         // Convert to integer adjustments
-        .map(|line| line.parse::<isize>().unwrap())
+        .map(|line| line.parse::<Int>().unwrap())
         // Cycle the adjustments list forever
         .cycle()
-        // Accumulate the adjustments
-        .scan(0, |freq, adjustment| {
-            *freq += adjustment;
-            Some(*freq)
+        // Accumulate the adjustments, stopping after an overflow
+        .scan(Ok(0), |freq: &mut Result<Int, Overflow>, adjustment| {
+            *freq = wide::add(1, freq.clone().ok()?, adjustment);
+            Some(freq.clone())
         })
-        // We only care about the one that doubles up
-        .filter(|freq| !frequencies.insert(*freq))
-        // We only need the one
-        .nth(0);
+        // We only care about the first one that doubles up (or the overflow)
+        .find(|freq| match freq {
+            Ok(freq) => !frequencies.insert(*freq),
+            Err(_) => true,
+        });
     answer.unwrap()
 }
 
@@ -47,5 +63,12 @@ fn do_part2(data: String) -> isize {
 fn test_part2() {
     let data: String = "+7\n+7\n-2\n-7\n-4".into();
     let answer = do_part2(data);
-    assert_eq!(answer, 14);
+    assert_eq!(answer, Ok(14));
+}
+
+#[test]
+fn test_part2_generated_input() {
+    // Generated adjustments sum to 0, so the frequency must repeat
+    let data = crate::generate::generate(1, 500, 3).unwrap();
+    do_part2(data).unwrap();
 }
@@ -0,0 +1,243 @@
+//! Problem: https://adventofcode.com/2018/day/10
+//! Points of light are moving across the sky at constant velocities, and at some point they line
+//! up to spell a message
+//! eg. position=< 9,  1> velocity=< 0,  2>
+//! Part 1: the message
+//! Part 2: how many seconds it takes to appear
+//!
+//! `--set visualize` animates the lights for a few seconds either side of the message
+//! (`--set visualize=N` for N seconds either side)
+
+use crate::animate;
+use crate::input::{read_input, InputError};
+use crate::options;
+use crate::stopwatch;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+/// The example from the puzzle text
+pub const EXAMPLES: &[&str] = &[include_str!("../data/examples/day10-1.txt")];
+
+/// How many seconds either side of the message `--set visualize` shows by default
+const VISUALIZE_SECONDS: usize = 5;
+
+/// The widest and tallest sky `--set visualize` will draw; before that it's just scattered dots
+const MAX_FRAME: (i64, i64) = (200, 60);
+
+/// A point of light
+#[derive(Clone, Debug, PartialEq)]
+struct Light {
+    x: i64,
+    y: i64,
+    dx: i64,
+    dy: i64,
+}
+
+impl FromStr for Light {
+    type Err = String;
+
+    /// eg. position=< 9,  1> velocity=< 0,  2>
+    fn from_str(s: &str) -> Result<Light, String> {
+        let numbers: Vec<i64> = s
+            .split(|ch: char| ch != '-' && !ch.is_ascii_digit())
+            .filter(|n| !n.is_empty())
+            .map(|n| n.parse::<i64>())
+            .collect::<Result<Vec<i64>, _>>()
+            .map_err(|err| format!("Unable to parse {}: {}", s, err))?;
+        match numbers[..] {
+            [x, y, dx, dy] => Ok(Light { x, y, dx, dy }),
+            _ => Err(format!("Expected a position and velocity: {}", s)),
+        }
+    }
+}
+
+/// Every light in the sky
+struct Sky {
+    lights: Vec<Light>,
+}
+
+impl FromStr for Sky {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Sky, String> {
+        let lights = s
+            .lines()
+            .map(|line| line.parse::<Light>())
+            .collect::<Result<Vec<Light>, String>>()?;
+        if lights.is_empty() {
+            return Err("There are no lights in the sky".to_string());
+        }
+        Ok(Sky { lights })
+    }
+}
+
+impl Sky {
+    /// Moves every light on by `seconds` (which can be negative, to go back in time)
+    fn step(&mut self, seconds: i64) {
+        for light in &mut self.lights {
+            light.x += light.dx * seconds;
+            light.y += light.dy * seconds;
+        }
+    }
+
+    /// The smallest rectangle holding every light: (min x, min y, max x, max y)
+    fn bounds(&self) -> (i64, i64, i64, i64) {
+        self.lights.iter().fold(
+            (i64::MAX, i64::MAX, i64::MIN, i64::MIN),
+            |(min_x, min_y, max_x, max_y), light| {
+                (
+                    min_x.min(light.x),
+                    min_y.min(light.y),
+                    max_x.max(light.x),
+                    max_y.max(light.y),
+                )
+            },
+        )
+    }
+
+    /// The area of the bounding box
+    fn area(&self) -> i64 {
+        let (min_x, min_y, max_x, max_y) = self.bounds();
+        (max_x - min_x + 1) * (max_y - min_y + 1)
+    }
+
+    /// Moves the lights on until they're as close together as they get, which is when the message
+    /// appears. Returns how many seconds that took
+    /// Stops as soon as the lights stop getting closer, so lights that never move (or a single
+    /// light) give 0 rather than waiting forever
+    fn align(&mut self) -> usize {
+        let mut seconds = 0;
+        let mut area = self.area();
+        loop {
+            self.step(1);
+            let next_area = self.area();
+            if next_area >= area {
+                // They've stopped closing in, so the last second was the one
+                self.step(-1);
+                return seconds;
+            }
+            area = next_area;
+            seconds += 1;
+        }
+    }
+
+    /// Draws the lights as '#'s on a background of '.'s, trimmed to the bounding box
+    fn render(&self) -> String {
+        let (min_x, min_y, max_x, max_y) = self.bounds();
+        let lit: HashSet<(i64, i64)> = self.lights.iter().map(|l| (l.x, l.y)).collect();
+        (min_y..=max_y)
+            .map(|y| {
+                (min_x..=max_x)
+                    .map(|x| if lit.contains(&(x, y)) { '#' } else { '.' })
+                    .chain(Some('\n'))
+                    .collect::<String>()
+            })
+            .collect()
+    }
+
+    /// Like `render`, but just gives the size if the lights are too spread out to draw
+    fn picture(&self) -> String {
+        let (min_x, min_y, max_x, max_y) = self.bounds();
+        let (width, height) = (max_x - min_x + 1, max_y - min_y + 1);
+        if width > MAX_FRAME.0 || height > MAX_FRAME.1 {
+            format!("({} x {}: too spread out to draw)\n", width, height)
+        } else {
+            self.render()
+        }
+    }
+
+    /// Animates the sky from `around` seconds before the message (which appeared after `seconds`)
+    /// to `around` seconds after it, then puts the lights back where the message is
+    fn visualize(&mut self, seconds: usize, around: usize) {
+        let first = seconds.saturating_sub(around);
+        self.step(first as i64 - seconds as i64);
+        for second in first..=seconds + around {
+            animate::frame(&format!("Second {}", second), &self.picture());
+            self.step(1);
+        }
+        self.step(-(around as i64) - 1);
+    }
+}
+
+pub fn part1() -> Result<(), InputError> {
+    let mut sky: Sky = read_input(10)?.parse().unwrap();
+    stopwatch::lap("parse");
+    let seconds = sky.align();
+    let answer = sky.render();
+    stopwatch::lap("solve");
+    if options::enabled("visualize") {
+        let around = options::get("visualize")
+            .and_then(|around| around.parse().ok())
+            .unwrap_or(VISUALIZE_SECONDS);
+        sky.visualize(seconds, around);
+    }
+    print!("Day 10 (Part 1):\n{}", answer);
+    Ok(())
+}
+
+pub fn part2() -> Result<(), InputError> {
+    let mut sky: Sky = read_input(10)?.parse().unwrap();
+    stopwatch::lap("parse");
+    let answer = sky.align();
+    stopwatch::lap("solve");
+    println!("Day 10 (Part 2): {}", answer);
+    Ok(())
+}
+
+#[test]
+fn test_parse() {
+    assert_eq!(
+        "position=<-6, 10> velocity=< 2, -2>".parse::<Light>(),
+        Ok(Light {
+            x: -6,
+            y: 10,
+            dx: 2,
+            dy: -2
+        })
+    );
+}
+
+#[test]
+fn test_example() {
+    let mut sky: Sky = EXAMPLES[0].parse().unwrap();
+    assert_eq!(sky.align(), 3);
+    assert_eq!(
+        sky.render(),
+        concat!(
+            "#...#..###\n",
+            "#...#...#.\n",
+            "#...#...#.\n",
+            "#####...#.\n",
+            "#...#...#.\n",
+            "#...#...#.\n",
+            "#...#...#.\n",
+            "#...#..###\n",
+        )
+    );
+}
+
+#[test]
+fn test_lights_that_never_close_in() {
+    assert!("".parse::<Sky>().is_err());
+    let mut sky: Sky = "position=< 3, 4> velocity=< 1, -1>".parse().unwrap();
+    assert_eq!(sky.align(), 0);
+    assert_eq!(sky.render(), "#\n");
+    let mut sky: Sky = "position=< 0, 0> velocity=< 0, 0>\nposition=< 2, 0> velocity=< 0, 0>"
+        .parse()
+        .unwrap();
+    assert_eq!(sky.align(), 0);
+    assert_eq!(sky.render(), "#.#\n");
+}
+
+#[test]
+fn test_visualize_puts_the_lights_back() {
+    let mut sky: Sky = EXAMPLES[0].parse().unwrap();
+    let seconds = sky.align();
+    let message = sky.render();
+    sky.visualize(seconds, 2);
+    assert_eq!(sky.render(), message);
+    let sky: Sky = "position=< 0, 0> velocity=< 0, 0>\nposition=< 500, 0> velocity=< 0, 0>"
+        .parse()
+        .unwrap();
+    assert_eq!(sky.picture(), "(501 x 1: too spread out to draw)\n");
+}
@@ -0,0 +1,233 @@
+//! Problem: https://adventofcode.com/2018/day/11
+//! A 300x300 grid of fuel cells, each with a power level worked out from its position and the grid
+//! serial number (the puzzle input)
+//! Part 1: the top left of the 3x3 square with the most power
+//! Part 2: the top left and size of the square (of any size) with the most power
+//!
+//! `--set grid-size=N` uses an NxN grid instead. `PowerGrid` gives access to every cell's power
+//! and to the summed-area table, and finds the best square over any range of sizes
+
+use crate::input::{read_input, InputError};
+use crate::options;
+use crate::stopwatch;
+use std::cmp::Reverse;
+use std::ops::RangeInclusive;
+
+/// The examples from the puzzle text (grid serial numbers)
+pub const EXAMPLES: &[&str] = &[
+    include_str!("../data/examples/day11-1.txt"),
+    include_str!("../data/examples/day11-2.txt"),
+];
+
+/// The width and height of the puzzle's grid
+const SIZE: usize = 300;
+
+/// Returns the grid size chosen with `--set grid-size=N`, or the puzzle's
+fn grid_size() -> usize {
+    options::get("grid-size").map_or(SIZE, |size| {
+        size.parse()
+            .unwrap_or_else(|_| panic!("Unable to parse grid-size={}", size))
+    })
+}
+
+/// Returns the power level of the fuel cell at x,y (both from 1)
+fn power_level(x: usize, y: usize, serial: i64) -> i64 {
+    let rack_id = x as i64 + 10;
+    let power = (rack_id * y as i64 + serial) * rack_id;
+    // Keep only the hundreds digit
+    (power / 100) % 10 - 5
+}
+
+/// A grid's power levels, and a summed-area table of them
+/// Each entry in the table holds the total power of every cell above and to the left of it
+/// (inclusive), so the power of any square can be found from its four corners, whatever its size
+pub struct PowerGrid {
+    /// The width and height of the grid
+    size: usize,
+    /// Each cell's power, a row at a time from 1,1
+    cells: Vec<i64>,
+    /// (size + 1) x (size + 1), with a row and column of zeros at the top and left
+    sums: Vec<i64>,
+}
+
+impl PowerGrid {
+    /// Works out the power of every cell in a `size` x `size` grid
+    pub fn new(serial: i64, size: usize) -> PowerGrid {
+        let cells: Vec<i64> = (1..=size)
+            .flat_map(|y| (1..=size).map(move |x| power_level(x, y, serial)))
+            .collect();
+        let width = size + 1;
+        let mut sums = vec![0; width * width];
+        for y in 1..=size {
+            for x in 1..=size {
+                sums[y * width + x] = cells[(y - 1) * size + x - 1]
+                    + sums[(y - 1) * width + x]
+                    + sums[y * width + x - 1]
+                    - sums[(y - 1) * width + x - 1];
+            }
+        }
+        PowerGrid { size, cells, sums }
+    }
+
+    /// The width and height of the grid
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Every cell's power, a row at a time from 1,1 (so x,y is at `(y - 1) * size + x - 1`)
+    pub fn cells(&self) -> &[i64] {
+        &self.cells
+    }
+
+    /// The summed-area table, laid out like `cells` but one wider and taller: x,y is at
+    /// `y * (size + 1) + x`, and row and column 0 are all zeros
+    pub fn sums(&self) -> &[i64] {
+        &self.sums
+    }
+
+    /// Returns the total power of the `size` x `size` square with its top left at x,y
+    pub fn square(&self, x: usize, y: usize, size: usize) -> i64 {
+        let width = self.size + 1;
+        let (left, top) = (x - 1, y - 1);
+        let (right, bottom) = (left + size, top + size);
+        self.sums[bottom * width + right]
+            - self.sums[top * width + right]
+            - self.sums[bottom * width + left]
+            + self.sums[top * width + left]
+    }
+
+    /// Returns (power, x, y) of the most powerful `size` x `size` square, or None if the grid
+    /// is too small to hold one
+    pub fn best_square(&self, size: usize) -> Option<(i64, usize, usize)> {
+        let last = (self.size + 1).checked_sub(size)?;
+        (1..=last)
+            .flat_map(|y| (1..=last).map(move |x| (x, y)))
+            .map(|(x, y)| (self.square(x, y, size), x, y))
+            // Break ties by taking the first square (in reading order)
+            .max_by_key(|&(power, x, y)| (power, Reverse((y, x))))
+    }
+
+    /// Returns (power, x, y, size) of the most powerful square with a size in `sizes`, or None
+    /// if none of them fit
+    /// Ties go to the smallest square
+    pub fn best_square_in(
+        &self,
+        sizes: RangeInclusive<usize>,
+    ) -> Option<(i64, usize, usize, usize)> {
+        sizes
+            .filter(|&size| size > 0)
+            .filter_map(|size| {
+                let (power, x, y) = self.best_square(size)?;
+                Some((power, x, y, size))
+            })
+            .max_by_key(|&(power, _, _, size)| (power, Reverse(size)))
+    }
+}
+
+fn parse(data: &str) -> i64 {
+    data.trim().parse().unwrap()
+}
+
+fn do_part1(serial: i64, grid_size: usize) -> String {
+    let grid = PowerGrid::new(serial, grid_size);
+    let (_, x, y) = grid
+        .best_square(3)
+        .unwrap_or_else(|| panic!("A {0}x{0} grid is too small for a 3x3 square", grid_size));
+    // Show the square's cells, like the puzzle text does
+    for row in y..y + 3 {
+        let start = (row - 1) * grid.size() + x - 1;
+        explain!("{:?}", &grid.cells()[start..start + 3]);
+    }
+    format!("{},{}", x, y)
+}
+
+pub fn part1() -> Result<(), InputError> {
+    let serial = parse(&read_input(11)?);
+    let answer = do_part1(serial, grid_size());
+    stopwatch::lap("solve");
+    println!("Day 11 (Part 1): {}", answer);
+    Ok(())
+}
+
+fn do_part2(serial: i64, grid_size: usize) -> String {
+    let grid = PowerGrid::new(serial, grid_size);
+    let (power, x, y, size) = grid
+        .best_square_in(1..=grid.size())
+        .unwrap_or_else(|| panic!("A {0}x{0} grid has no squares", grid_size));
+    // The last entry in the summed-area table covers the whole grid
+    explain!("The whole grid has {} power", grid.sums().last().unwrap());
+    explain!(
+        "The {}x{} square at {},{} has {} power",
+        size,
+        size,
+        x,
+        y,
+        power
+    );
+    format!("{},{},{}", x, y, size)
+}
+
+pub fn part2() -> Result<(), InputError> {
+    let serial = parse(&read_input(11)?);
+    let answer = do_part2(serial, grid_size());
+    stopwatch::lap("solve");
+    println!("Day 11 (Part 2): {}", answer);
+    Ok(())
+}
+
+#[test]
+fn test_power_level() {
+    assert_eq!(power_level(3, 5, 8), 4);
+    assert_eq!(power_level(122, 79, 57), -5);
+    assert_eq!(power_level(217, 196, 39), 0);
+    assert_eq!(power_level(101, 153, 71), 4);
+}
+
+#[test]
+fn test_square() {
+    let grid = PowerGrid::new(18, SIZE);
+    assert_eq!(grid.square(33, 45, 3), 29);
+    assert_eq!(grid.square(90, 269, 16), 113);
+    // A 1x1 square is just the cell
+    assert_eq!(grid.square(3, 5, 1), power_level(3, 5, 18));
+    assert_eq!(grid.cells()[(5 - 1) * SIZE + 3 - 1], power_level(3, 5, 18));
+    assert_eq!(grid.sums()[1], 0);
+    assert_eq!(grid.sums()[SIZE + 2], power_level(1, 1, 18));
+}
+
+#[test]
+fn test_other_grid_sizes() {
+    // The puzzle's 3x3 answer for serial 18 is in the top left 50x50 cells
+    let grid = PowerGrid::new(18, 50);
+    assert_eq!(grid.size(), 50);
+    assert_eq!(grid.best_square(3), Some((29, 33, 45)));
+    assert_eq!(grid.best_square(51), None);
+    // Every size, compared with adding up each square cell by cell
+    let grid = PowerGrid::new(42, 12);
+    let (power, x, y, size) = grid.best_square_in(0..=20).unwrap();
+    let cell = |x: usize, y: usize| grid.cells()[(y - 1) * 12 + x - 1];
+    let brute_force = (1..=12)
+        .flat_map(|size| (1..=13 - size).map(move |y| (size, y)))
+        .flat_map(|(size, y)| (1..=13 - size).map(move |x| (size, x, y)))
+        .map(|(size, x, y)| {
+            let total: i64 = (y..y + size)
+                .flat_map(|cy| (x..x + size).map(move |cx| (cx, cy)))
+                .map(|(cx, cy)| cell(cx, cy))
+                .sum();
+            total
+        })
+        .max()
+        .unwrap();
+    assert_eq!(power, brute_force);
+    assert_eq!(grid.square(x, y, size), power);
+    assert_eq!(PowerGrid::new(42, 2).best_square_in(3..=5), None);
+}
+
+#[test]
+fn test_examples() {
+    let serials: Vec<i64> = EXAMPLES.iter().map(|example| parse(example)).collect();
+    assert_eq!(do_part1(serials[0], SIZE), "33,45");
+    assert_eq!(do_part1(serials[1], SIZE), "21,61");
+    assert_eq!(do_part2(serials[0], SIZE), "90,269,16");
+    assert_eq!(do_part2(serials[1], SIZE), "232,251,12");
+}
@@ -0,0 +1,174 @@
+//! Problem: https://adventofcode.com/2018/day/12
+//! A row of pots, each with or without a plant, changes every generation according to rules that
+//! look at each pot and the two either side of it
+//! eg. initial state: #..#.#..##......###...###
+//! ...## => #
+//! Part 1: the sum of the numbers of the pots with plants after 20 generations
+//! Part 2: the same after fifty billion generations
+//!
+//! The pots are a one-dimensional cellular automaton; see `automaton`
+
+use crate::automaton::{Automaton1D, Rules};
+use crate::input::{read_input, InputError};
+use crate::stopwatch;
+use crate::wide::{self, Int, Overflow};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// The example from the puzzle text
+pub const EXAMPLES: &[&str] = &[include_str!("../data/examples/day12-1.txt")];
+
+/// How many generations part 2 asks about
+const LONG_TIME: Int = 50_000_000_000;
+
+/// Returns the sum of the numbers of the pots with plants in them
+fn sum(pots: &Automaton1D) -> Result<Int, Overflow> {
+    pots.cells()
+        .iter()
+        .enumerate()
+        .filter(|(_, &plant)| plant)
+        .try_fold(0, |sum, (i, _)| {
+            let pot = wide::checked_add(12, pots.first(), i as Int)?;
+            wide::add(12, sum, pot)
+        })
+}
+
+/// The puzzle input: the initial state and the rules, as an automaton where live cells are pots
+/// with plants in them
+struct Tunnel {
+    pots: Automaton1D,
+}
+
+impl FromStr for Tunnel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Tunnel, String> {
+        let (initial, rules) = s.split_once('\n').unwrap_or((s, ""));
+        let initial = initial
+            .strip_prefix("initial state:")
+            .ok_or("Expected the initial state on the first line")?;
+        let rules: Rules = rules.parse()?;
+        Ok(Tunnel {
+            pots: Automaton1D::new(initial, rules),
+        })
+    }
+}
+
+impl Tunnel {
+    /// Returns the sum of the plant pot numbers after `generations`
+    /// Patterns tend to settle down into a shape (or a short cycle of shapes) that slides along
+    /// the row, so once a shape comes round again, the rest of the slide is extrapolated
+    fn sum_after(&self, generations: Int) -> Result<Int, Overflow> {
+        // The generation each shape was first seen in, and where it started then
+        let mut seen: HashMap<Vec<bool>, (Int, Int)> = HashMap::new();
+        let mut pots = self.pots.clone();
+        let mut generation = 0;
+        while generation < generations {
+            if let Some(&(before, first)) = seen.get(pots.cells()) {
+                let period = generation - before;
+                let shift = pots.first() - first;
+                explain!(
+                    "The pattern slides {} pot(s) every {} generation(s) from generation {}:",
+                    shift,
+                    period,
+                    before
+                );
+                let pattern: String = pots
+                    .window(pots.first(), pots.cells().len())
+                    .iter()
+                    .map(|&plant| if plant { '#' } else { '.' })
+                    .collect();
+                explain!("{}", pattern);
+                // Skip every whole period, then run the few generations left over
+                // These get big in one go, so they're always checked
+                let remaining = generations - generation;
+                for _ in 0..remaining % period {
+                    pots.step();
+                }
+                let count = pots.cells().iter().filter(|&&p| p).count() as Int;
+                let slide = wide::checked_mul(12, remaining / period, shift)?;
+                let slide = wide::checked_mul(12, slide, count)?;
+                return wide::checked_add(12, sum(&pots)?, slide);
+            }
+            seen.insert(pots.cells().to_vec(), (generation, pots.first()));
+            pots.step();
+            generation += 1;
+        }
+        sum(&pots)
+    }
+}
+
+pub fn part1() -> Result<(), InputError> {
+    let tunnel: Tunnel = read_input(12)?.parse().unwrap();
+    stopwatch::lap("parse");
+    let answer = tunnel.sum_after(20)?;
+    stopwatch::lap("solve");
+    println!("Day 12 (Part 1): {}", answer);
+    Ok(())
+}
+
+pub fn part2() -> Result<(), InputError> {
+    let tunnel: Tunnel = read_input(12)?.parse().unwrap();
+    stopwatch::lap("parse");
+    let answer = tunnel.sum_after(LONG_TIME)?;
+    stopwatch::lap("solve");
+    println!("Day 12 (Part 2): {}", answer);
+    Ok(())
+}
+
+#[test]
+fn test_step() {
+    let tunnel: Tunnel = EXAMPLES[0].parse().unwrap();
+    let mut pots = tunnel.pots.clone();
+    pots.step();
+    // ...#...#....#.....#..#..#..#........... in the puzzle text, where the first pot is -3
+    let expected: Vec<bool> = "...#...#....#.....#..#..#..#..........."
+        .chars()
+        .map(|ch| ch == '#')
+        .collect();
+    assert_eq!(pots.window(-3, expected.len()), expected);
+    assert_eq!(pots.first(), 0);
+}
+
+#[test]
+fn test_example() {
+    let tunnel: Tunnel = EXAMPLES[0].parse().unwrap();
+    assert_eq!(tunnel.sum_after(20), Ok(325));
+    // The extrapolation should agree with just running the generations
+    let mut pots = tunnel.pots.clone();
+    for _ in 0..200 {
+        pots.step();
+    }
+    assert_eq!(tunnel.sum_after(200), sum(&pots));
+    assert_eq!(tunnel.sum_after(LONG_TIME), Ok(999_999_999_374));
+    // Too far into the future to count, even with the wide-int feature
+    // (the shift is 1, so it's the multiplication by the plant count that overflows)
+    assert!(matches!(
+        tunnel.sum_after(Int::MAX),
+        Err(Overflow {
+            day: 12,
+            op: '*',
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_period_two_slide() {
+    // A lone plant becomes two, which become one two pots further on: the shape only repeats
+    // every other generation
+    let tunnel: Tunnel = "initial state: #\n\n.#. => #\n#.. => #".parse().unwrap();
+    let mut pots = tunnel.pots.clone();
+    for generations in 0..30 {
+        assert_eq!(
+            tunnel.sum_after(generations),
+            sum(&pots),
+            "after {}",
+            generations
+        );
+        pots.step();
+    }
+    // Generation 2n has one plant at 2n, and 2n+1 has plants at 2n and 2n+1
+    assert_eq!(tunnel.sum_after(LONG_TIME), Ok(LONG_TIME));
+    assert_eq!(tunnel.sum_after(LONG_TIME + 1), Ok(LONG_TIME * 2 + 1));
+}
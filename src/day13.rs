@@ -0,0 +1,364 @@
+//! Problem: https://adventofcode.com/2018/day/13
+//! Carts run around a network of tracks, taking turns to move one square in reading order
+//! Each time a cart reaches an intersection it turns left, then goes straight on, then turns
+//! right, then starts again
+//! Part 1: where the first crash happens
+//! Part 2: where the last cart is, once every crashed cart has been removed
+//!
+//! `simulate` runs the carts as a stream of events (each crash, then the last cart standing), so
+//! both parts, the tests and anything drawing the carts can follow the same run
+//!
+//! `--set animate` draws the map at every crash during part 2, with the crash marked by an 'X'
+
+use crate::animate;
+use crate::input::{read_input, InputError};
+use crate::options;
+use crate::stopwatch;
+use std::collections::VecDeque;
+
+/// The examples from the puzzle text (one for each part)
+pub const EXAMPLES: &[&str] = &[
+    include_str!("../data/examples/day13-1.txt"),
+    include_str!("../data/examples/day13-2.txt"),
+];
+
+/// The map of the tracks, with the carts taken off
+struct Track {
+    rows: Vec<Vec<u8>>,
+}
+
+impl Track {
+    /// Returns the piece of track at x,y (a space if there's none)
+    fn at(&self, x: usize, y: usize) -> u8 {
+        self.rows
+            .get(y)
+            .and_then(|row| row.get(x))
+            .cloned()
+            .unwrap_or(b' ')
+    }
+}
+
+/// A cart somewhere on the track
+#[derive(Clone, Debug, PartialEq)]
+struct Cart {
+    /// Numbers the carts in reading order, as they are on the map
+    id: usize,
+    x: usize,
+    y: usize,
+    /// Which way the cart is heading (y grows downwards)
+    dx: isize,
+    dy: isize,
+    /// How many intersections the cart has been through
+    intersections: usize,
+}
+
+impl Cart {
+    fn turn_left(&mut self) {
+        let (dx, dy) = (self.dy, -self.dx);
+        self.dx = dx;
+        self.dy = dy;
+    }
+
+    fn turn_right(&mut self) {
+        let (dx, dy) = (-self.dy, self.dx);
+        self.dx = dx;
+        self.dy = dy;
+    }
+
+    /// Moves the cart one square, then turns it to follow the track it landed on
+    fn advance(&mut self, track: &Track) {
+        self.x = (self.x as isize + self.dx) as usize;
+        self.y = (self.y as isize + self.dy) as usize;
+        match track.at(self.x, self.y) {
+            b'/' if self.dx == 0 => self.turn_right(),
+            b'/' => self.turn_left(),
+            b'\\' if self.dx == 0 => self.turn_left(),
+            b'\\' => self.turn_right(),
+            b'+' => {
+                match self.intersections % 3 {
+                    0 => self.turn_left(),
+                    1 => (),
+                    _ => self.turn_right(),
+                }
+                self.intersections += 1;
+            }
+            b'-' | b'|' => (),
+            other => panic!(
+                "Cart ran off the track onto '{}' at {},{}",
+                other as char, self.x, self.y
+            ),
+        }
+    }
+}
+
+/// Splits the map into the track and the carts on it
+fn parse(data: &str) -> (Track, Vec<Cart>) {
+    let mut carts = Vec::new();
+    let rows = data
+        .lines()
+        .enumerate()
+        .map(|(y, line)| {
+            line.bytes()
+                .enumerate()
+                .map(|(x, piece)| {
+                    let (dx, dy, under) = match piece {
+                        b'>' => (1, 0, b'-'),
+                        b'<' => (-1, 0, b'-'),
+                        b'v' => (0, 1, b'|'),
+                        b'^' => (0, -1, b'|'),
+                        _ => return piece,
+                    };
+                    carts.push(Cart {
+                        id: carts.len(),
+                        x,
+                        y,
+                        dx,
+                        dy,
+                        intersections: 0,
+                    });
+                    under
+                })
+                .collect()
+        })
+        .collect();
+    (Track { rows }, carts)
+}
+
+/// Moves every cart once, in reading order, removing any that crash
+/// Returns where the crashes happened, and which carts crashed (the one that moved first), in the
+/// order they happened
+fn tick(track: &Track, carts: &mut Vec<Cart>) -> Vec<(usize, usize, [usize; 2])> {
+    carts.sort_by_key(|cart| (cart.y, cart.x));
+    let mut crashed = vec![false; carts.len()];
+    let mut crashes = Vec::new();
+    for i in 0..carts.len() {
+        if crashed[i] {
+            continue;
+        }
+        carts[i].advance(track);
+        let (x, y) = (carts[i].x, carts[i].y);
+        let hit =
+            (0..carts.len()).find(|&j| j != i && !crashed[j] && (carts[j].x, carts[j].y) == (x, y));
+        if let Some(j) = hit {
+            crashed[i] = true;
+            crashed[j] = true;
+            crashes.push((x, y, [carts[i].id, carts[j].id]));
+        }
+    }
+    let mut crashed = crashed.into_iter();
+    carts.retain(|_| !crashed.next().unwrap());
+    crashes
+}
+
+/// Something that happened while the carts were running
+#[derive(Debug, PartialEq)]
+pub enum Event {
+    /// Two carts (numbered in reading order on the map) crashed into each other at x,y during
+    /// `tick` (from 1), and were removed
+    Crash {
+        tick: usize,
+        x: usize,
+        y: usize,
+        carts: [usize; 2],
+    },
+    /// Every other cart has crashed; this one was left at x,y after `tick`
+    LastCart { tick: usize, x: usize, y: usize },
+    /// Every cart crashed, after `tick`
+    NoneLeft { tick: usize },
+}
+
+/// Runs the carts until there's at most one left, yielding what happens along the way
+/// Never ends if two or more carts go round without ever crashing
+pub struct Simulation {
+    track: Track,
+    carts: Vec<Cart>,
+    /// How many ticks have been run
+    ticks: usize,
+    /// Events from the last tick that haven't been handed out yet
+    pending: VecDeque<Event>,
+    finished: bool,
+}
+
+impl Iterator for Simulation {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        while self.pending.is_empty() && !self.finished {
+            if self.carts.len() > 1 {
+                self.ticks += 1;
+                let tick = self.ticks;
+                self.pending.extend(
+                    self::tick(&self.track, &mut self.carts)
+                        .into_iter()
+                        .map(|(x, y, carts)| Event::Crash { tick, x, y, carts }),
+                );
+            } else {
+                self.finished = true;
+                self.pending.push_back(match self.carts.first() {
+                    Some(cart) => Event::LastCart {
+                        tick: self.ticks,
+                        x: cart.x,
+                        y: cart.y,
+                    },
+                    None => Event::NoneLeft { tick: self.ticks },
+                });
+            }
+        }
+        self.pending.pop_front()
+    }
+}
+
+impl Simulation {
+    /// Draws the track with the carts on it as they are now, like the puzzle does, with an 'X'
+    /// at each of `crashes`
+    fn render(&self, crashes: &[(usize, usize)]) -> String {
+        let mut rows = self.track.rows.clone();
+        for cart in &self.carts {
+            rows[cart.y][cart.x] = match (cart.dx, cart.dy) {
+                (1, _) => b'>',
+                (-1, _) => b'<',
+                (_, 1) => b'v',
+                _ => b'^',
+            };
+        }
+        for &(x, y) in crashes {
+            rows[y][x] = b'X';
+        }
+        rows.iter()
+            .map(|row| String::from_utf8_lossy(row).trim_end().to_string() + "\n")
+            .collect()
+    }
+}
+
+/// Starts the carts on the map in `data`
+pub fn simulate(data: &str) -> Simulation {
+    let (track, carts) = parse(data);
+    Simulation {
+        track,
+        carts,
+        ticks: 0,
+        pending: VecDeque::new(),
+        finished: false,
+    }
+}
+
+fn do_part1(data: &str) -> String {
+    simulate(data)
+        .find_map(|event| match event {
+            Event::Crash { x, y, .. } => Some(format!("{},{}", x, y)),
+            _ => None,
+        })
+        .unwrap_or_else(|| "no carts crashed".to_string())
+}
+
+pub fn part1() -> Result<(), InputError> {
+    let data = read_input(13)?;
+    let answer = do_part1(&data);
+    stopwatch::lap("solve");
+    println!("Day 13 (Part 1): {}", answer);
+    Ok(())
+}
+
+fn do_part2(data: &str) -> String {
+    let animating = options::enabled("animate");
+    let mut simulation = simulate(data);
+    while let Some(event) = simulation.next() {
+        match event {
+            Event::Crash { tick, x, y, carts } => {
+                let caption = format!(
+                    "Tick {}: carts {} and {} crashed at {},{}",
+                    tick, carts[0], carts[1], x, y
+                );
+                if animating {
+                    animate::frame(&caption, &simulation.render(&[(x, y)]));
+                }
+                explain!("{}", caption);
+            }
+            Event::LastCart { tick, x, y } => {
+                if animating {
+                    let caption = format!("Tick {}: the last cart is at {},{}", tick, x, y);
+                    animate::frame(&caption, &simulation.render(&[]));
+                }
+                return format!("{},{}", x, y);
+            }
+            Event::NoneLeft { .. } => return "every cart crashed".to_string(),
+        }
+    }
+    unreachable!("The simulation always ends with the last cart or none")
+}
+
+pub fn part2() -> Result<(), InputError> {
+    let data = read_input(13)?;
+    let answer = do_part2(&data);
+    stopwatch::lap("solve");
+    println!("Day 13 (Part 2): {}", answer);
+    Ok(())
+}
+
+#[test]
+fn test_tick() {
+    let (track, mut carts) = parse(EXAMPLES[0]);
+    assert_eq!(track.at(2, 0), b'-');
+    assert_eq!(carts.len(), 2);
+    assert_eq!((carts[1].id, carts[1].y), (1, 3));
+    assert!(tick(&track, &mut carts).is_empty());
+    // The top cart carries on right; the other goes down onto an intersection and turns left
+    assert_eq!((carts[0].x, carts[0].y, carts[0].dx), (3, 0, 1));
+    assert_eq!((carts[1].x, carts[1].y), (9, 4));
+    assert_eq!(
+        (carts[1].dx, carts[1].dy, carts[1].intersections),
+        (1, 0, 1)
+    );
+}
+
+#[test]
+fn test_examples() {
+    assert_eq!(do_part1(EXAMPLES[0]), "7,3");
+    assert_eq!(do_part2(EXAMPLES[1]), "6,4");
+}
+
+#[test]
+fn test_events() {
+    let events: Vec<Event> = simulate(EXAMPLES[1]).collect();
+    let crash = |tick, x, y, carts| Event::Crash { tick, x, y, carts };
+    assert_eq!(
+        events,
+        [
+            crash(1, 2, 0, [1, 0]),
+            crash(1, 2, 4, [5, 4]),
+            crash(1, 6, 4, [6, 3]),
+            crash(3, 2, 4, [7, 2]),
+            Event::LastCart {
+                tick: 3,
+                x: 6,
+                y: 4
+            },
+        ]
+    );
+    // The first example's only two carts crash on tick 14, leaving none
+    let events: Vec<Event> = simulate(EXAMPLES[0]).collect();
+    assert_eq!(
+        events,
+        [crash(14, 7, 3, [0, 1]), Event::NoneLeft { tick: 14 }]
+    );
+}
+
+#[test]
+fn test_render() {
+    let mut simulation = simulate(EXAMPLES[1]);
+    assert_eq!(simulation.render(&[]), EXAMPLES[1].replace("\r\n", "\n"));
+    // The first tick has three crashes, all at once; the puzzle draws the same map after it
+    simulation.next();
+    assert_eq!(
+        simulation.render(&[(2, 0), (2, 4), (6, 4)]),
+        concat!(
+            "/-X-\\\n",
+            "|   |\n",
+            "| v-+-\\\n",
+            "| | | |\n",
+            "\\-X-/ X\n",
+            "  |   |\n",
+            "  ^---^\n",
+        )
+    );
+}
@@ -0,0 +1,158 @@
+//! Problem: https://adventofcode.com/2018/day/14
+//! Two elves make hot chocolate recipes. Each round they add the digits of the sum of their
+//! current recipes' scores to the scoreboard, then each moves on 1 + their recipe's score
+//! The scoreboard starts as 3 7
+//! Part 1: the scores of the ten recipes after the number in the input
+//! Part 2: how many recipes come before the input's digits first appear on the scoreboard
+//!
+//! `Scoreboard` is an endless iterator over the scores, and `find_pattern` searches it for any
+//! run of digits
+
+use crate::input::{read_input, InputError};
+use crate::stopwatch;
+
+/// The examples from the puzzle text (one for each part)
+pub const EXAMPLES: &[&str] = &[
+    include_str!("../data/examples/day14-1.txt"),
+    include_str!("../data/examples/day14-2.txt"),
+];
+
+/// The recipe scores, and where the elves are up to
+/// As an iterator, it hands out every score in order (starting with the 3 and 7), making more
+/// recipes as it needs them
+pub struct Scoreboard {
+    scores: Vec<u8>,
+    elves: [usize; 2],
+    /// How many scores the iterator has handed out
+    handed_out: usize,
+}
+
+impl Scoreboard {
+    pub fn new() -> Scoreboard {
+        Scoreboard {
+            scores: vec![3, 7],
+            elves: [0, 1],
+            handed_out: 0,
+        }
+    }
+
+    /// Makes new recipes and moves the elves on, returning how many recipes were added (1 or 2)
+    fn step(&mut self) -> usize {
+        let sum = self.elves.iter().map(|&elf| self.scores[elf]).sum::<u8>();
+        let added = if sum >= 10 {
+            self.scores.push(sum / 10);
+            2
+        } else {
+            1
+        };
+        self.scores.push(sum % 10);
+        let len = self.scores.len();
+        for elf in self.elves.iter_mut() {
+            *elf = (*elf + 1 + self.scores[*elf] as usize) % len;
+        }
+        added
+    }
+}
+
+impl Iterator for Scoreboard {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        while self.handed_out >= self.scores.len() {
+            self.step();
+        }
+        self.handed_out += 1;
+        Some(self.scores[self.handed_out - 1])
+    }
+}
+
+/// Returns how many recipes come before `digits` (each 0 to 9) first appear on the scoreboard
+/// Each score is checked as it's made, so the board is never searched more than once
+/// Never returns if the digits can't appear, so any digit over 9 panics
+pub fn find_pattern(digits: &[u8]) -> usize {
+    assert!(
+        digits.iter().all(|&digit| digit <= 9),
+        "Scores are single digits: {:?}",
+        digits
+    );
+    let mut board = Scoreboard::new();
+    // The scores handed out so far
+    let mut seen = 0;
+    while !board.scores[..seen].ends_with(digits) {
+        board.next();
+        seen += 1;
+    }
+    seen - digits.len()
+}
+
+fn do_part1(after: usize) -> String {
+    Scoreboard::new()
+        .skip(after)
+        .take(10)
+        .map(|score| score.to_string())
+        .collect()
+}
+
+pub fn part1() -> Result<(), InputError> {
+    let after = read_input(14)?.trim().parse().unwrap();
+    let answer = do_part1(after);
+    stopwatch::lap("solve");
+    println!("Day 14 (Part 1): {}", answer);
+    Ok(())
+}
+
+fn do_part2(digits: &str) -> usize {
+    let digits: Vec<u8> = digits.trim().bytes().map(|digit| digit - b'0').collect();
+    find_pattern(&digits)
+}
+
+pub fn part2() -> Result<(), InputError> {
+    let data = read_input(14)?;
+    let answer = do_part2(&data);
+    stopwatch::lap("solve");
+    println!("Day 14 (Part 2): {}", answer);
+    Ok(())
+}
+
+#[test]
+fn test_step() {
+    let mut board = Scoreboard::new();
+    assert_eq!(board.step(), 2);
+    assert_eq!(board.scores, vec![3, 7, 1, 0]);
+    assert_eq!(board.elves, [0, 1]);
+    assert_eq!(board.step(), 2);
+    assert_eq!(board.scores, vec![3, 7, 1, 0, 1, 0]);
+    assert_eq!(board.elves, [4, 3]);
+    assert_eq!(board.step(), 1);
+    assert_eq!(board.scores, vec![3, 7, 1, 0, 1, 0, 1]);
+}
+
+#[test]
+fn test_examples() {
+    assert_eq!(do_part1(9), "5158916779");
+    assert_eq!(do_part1(5), "0124515891");
+    assert_eq!(do_part1(18), "9251071085");
+    assert_eq!(do_part1(2018), "5941429882");
+    assert_eq!(do_part2("51589"), 9);
+    assert_eq!(do_part2("01245"), 5);
+    assert_eq!(do_part2("92510"), 18);
+    assert_eq!(do_part2("59414"), 2018);
+}
+
+#[test]
+fn test_scoreboard_iterator() {
+    let scores: Vec<u8> = Scoreboard::new().take(20).collect();
+    assert_eq!(
+        scores,
+        [3, 7, 1, 0, 1, 0, 1, 2, 4, 5, 1, 5, 8, 9, 1, 6, 7, 7, 9, 2]
+    );
+}
+
+#[test]
+fn test_find_pattern() {
+    // Patterns of any length, including ones shorter and longer than the puzzle's
+    assert_eq!(find_pattern(&[]), 0);
+    assert_eq!(find_pattern(&[3, 7]), 0);
+    assert_eq!(find_pattern(&[0, 1, 2]), 5);
+    assert_eq!(find_pattern(&[5, 9, 4, 1, 4, 2, 9, 8, 8, 2]), 2018);
+}
@@ -0,0 +1,407 @@
+//! Problem: https://adventofcode.com/2018/day/15
+//! Elves (E) and goblins (G) fight in a cave. Each round, every unit (in reading order) moves
+//! one step along the shortest path towards the nearest enemy, then attacks the adjacent enemy
+//! with the fewest hit points
+//! The outcome is the number of full rounds times the hit points the winners have left
+//! Part 1: the outcome of the combat
+//! Part 2: the outcome when the elves have the lowest attack power that lets them all survive
+//!
+//! `--set replay` draws the cave after every round, like the puzzle does, with each unit's hit
+//! points beside its row. Units hit that round are shown in lower case, and ones killed as 'x'
+//! Part 2 replays the fight at the attack power that wins
+
+use crate::animate;
+use crate::executor;
+use crate::input::{read_input, InputError};
+use crate::options;
+use crate::stopwatch;
+use crate::trace;
+use std::collections::{HashSet, VecDeque};
+
+/// The examples from the puzzle text
+pub const EXAMPLES: &[&str] = &[
+    include_str!("../data/examples/day15-1.txt"),
+    include_str!("../data/examples/day15-2.txt"),
+    include_str!("../data/examples/day15-3.txt"),
+    include_str!("../data/examples/day15-4.txt"),
+    include_str!("../data/examples/day15-5.txt"),
+    include_str!("../data/examples/day15-6.txt"),
+];
+
+/// Every unit starts with this many hit points
+const HIT_POINTS: i32 = 200;
+
+/// Goblins (and elves, in part 1) do this much damage
+const ATTACK_POWER: i32 = 3;
+
+/// A square in the cave: (row, column), so that they sort in reading order
+type Pos = (usize, usize);
+
+/// Returns the squares next to `pos`, in reading order
+fn neighbours((y, x): Pos) -> [Pos; 4] {
+    [(y - 1, x), (y, x - 1), (y, x + 1), (y + 1, x)]
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Race {
+    Elf,
+    Goblin,
+}
+
+#[derive(Clone, Debug)]
+struct Unit {
+    race: Race,
+    pos: Pos,
+    hp: i32,
+}
+
+/// The walls of the cave (the units are kept separately)
+struct Cave {
+    walls: Vec<Vec<bool>>,
+}
+
+impl Cave {
+    fn is_wall(&self, (y, x): Pos) -> bool {
+        self.walls
+            .get(y)
+            .and_then(|row| row.get(x))
+            .cloned()
+            .unwrap_or(true)
+    }
+
+    /// Returns the distance from `from` to every square reachable from it, avoiding walls and the
+    /// squares in `blocked`
+    fn distances(&self, from: Pos, blocked: &HashSet<Pos>) -> Vec<Vec<Option<usize>>> {
+        let mut distances = vec![vec![None; self.walls[0].len()]; self.walls.len()];
+        distances[from.0][from.1] = Some(0);
+        let mut queue = VecDeque::new();
+        queue.push_back((from, 0));
+        while let Some((pos, distance)) = queue.pop_front() {
+            for next in neighbours(pos).iter() {
+                if self.is_wall(*next)
+                    || blocked.contains(next)
+                    || distances[next.0][next.1].is_some()
+                {
+                    continue;
+                }
+                distances[next.0][next.1] = Some(distance + 1);
+                queue.push_back((*next, distance + 1));
+            }
+        }
+        distances
+    }
+}
+
+/// Reads the map into the cave and the units in it
+fn parse(data: &str) -> (Cave, Vec<Unit>) {
+    let mut units = Vec::new();
+    let walls = data
+        .lines()
+        .enumerate()
+        .map(|(y, line)| {
+            line.chars()
+                .enumerate()
+                .map(|(x, square)| {
+                    let race = match square {
+                        'E' => Race::Elf,
+                        'G' => Race::Goblin,
+                        _ => return square == '#',
+                    };
+                    units.push(Unit {
+                        race,
+                        pos: (y, x),
+                        hp: HIT_POINTS,
+                    });
+                    false
+                })
+                .collect()
+        })
+        .collect();
+    (Cave { walls }, units)
+}
+
+/// How a combat ended
+#[derive(Debug, PartialEq)]
+struct Outcome {
+    /// Full rounds completed
+    rounds: usize,
+    /// Total hit points of the units left standing
+    hp_left: i32,
+    winner: Race,
+    /// True if any elf was killed
+    elf_died: bool,
+}
+
+impl Outcome {
+    fn score(&self) -> i32 {
+        self.rounds as i32 * self.hp_left
+    }
+}
+
+/// Draws the cave with the units in it, each row followed by the hit points of its units
+/// `attacked` (indexed like `units`) says who was hit this round; they're drawn in lower case,
+/// and units that have been killed (but not yet removed) as an 'x'
+fn render(cave: &Cave, units: &[Unit], attacked: &[bool]) -> String {
+    let mut picture = String::new();
+    for (y, row) in cave.walls.iter().enumerate() {
+        let mut squares: Vec<char> = row
+            .iter()
+            .map(|&wall| if wall { '#' } else { '.' })
+            .collect();
+        // The dead go first, so a unit that has since stepped onto their square is drawn over them
+        let mut in_row: Vec<(usize, &Unit)> = units
+            .iter()
+            .enumerate()
+            .filter(|(_, unit)| unit.pos.0 == y)
+            .collect();
+        in_row.sort_by_key(|(_, unit)| (unit.hp > 0, unit.pos.1));
+        let mut hit_points = Vec::new();
+        for (i, unit) in in_row {
+            let letter = match unit.race {
+                Race::Elf => 'E',
+                Race::Goblin => 'G',
+            };
+            squares[unit.pos.1] = if unit.hp <= 0 {
+                'x'
+            } else if attacked.get(i) == Some(&true) {
+                letter.to_ascii_lowercase()
+            } else {
+                letter
+            };
+            if unit.hp > 0 {
+                hit_points.push(format!("{}({})", letter, unit.hp));
+            }
+        }
+        picture.extend(squares);
+        if !hit_points.is_empty() {
+            picture += "   ";
+            picture += &hit_points.join(", ");
+        }
+        picture.push('\n');
+    }
+    picture
+}
+
+/// Returns where `unit` should step to get closer to its nearest enemy, if anywhere
+fn choose_step(cave: &Cave, units: &[Unit], unit: &Unit) -> Option<Pos> {
+    let occupied: HashSet<Pos> = units.iter().filter(|u| u.hp > 0).map(|u| u.pos).collect();
+    let from_unit = cave.distances(unit.pos, &occupied);
+    let distance = |distances: &Vec<Vec<Option<usize>>>, pos: Pos| distances[pos.0][pos.1];
+    // The nearest open square next to an enemy; ties go to the first in reading order
+    let target = units
+        .iter()
+        .filter(|u| u.hp > 0 && u.race != unit.race)
+        .flat_map(|enemy| neighbours(enemy.pos).to_vec())
+        .filter_map(|pos| distance(&from_unit, pos).map(|d| (d, pos)))
+        .min()?
+        .1;
+    // Take the first step (in reading order) of a shortest path to it
+    let to_target = cave.distances(target, &occupied);
+    neighbours(unit.pos)
+        .iter()
+        .filter_map(|&pos| distance(&to_target, pos).map(|d| (d, pos)))
+        .min()
+        .map(|(_, pos)| pos)
+}
+
+/// Runs a whole combat, with the elves hitting for `elf_attack`
+/// If `stop_if_elf_dies` is set, the combat ends early as soon as an elf is killed, and the
+/// goblins are counted as the winners
+/// If `replay` is set, the cave is drawn after every round
+fn combat(
+    cave: &Cave,
+    mut units: Vec<Unit>,
+    elf_attack: i32,
+    stop_if_elf_dies: bool,
+    replay: bool,
+) -> Outcome {
+    let _span = trace::span("combat").arg("elf attack", elf_attack);
+    let mut rounds = 0;
+    let mut elf_died = false;
+    if replay {
+        animate::frame("Initially:", &render(cave, &units, &[]));
+    }
+    loop {
+        let _round = trace::span("round").arg("round", rounds + 1);
+        units.sort_by_key(|unit| unit.pos);
+        let mut attacked = vec![false; units.len()];
+        for i in 0..units.len() {
+            if units[i].hp <= 0 {
+                continue;
+            }
+            let race = units[i].race;
+            if !units.iter().any(|u| u.hp > 0 && u.race != race) {
+                // Nobody left to fight, so the combat ends part way through the round
+                let hp_left = units.iter().filter(|u| u.hp > 0).map(|u| u.hp).sum();
+                if replay {
+                    let caption = format!("The combat ends during round {}:", rounds + 1);
+                    animate::frame(&caption, &render(cave, &units, &attacked));
+                }
+                return Outcome {
+                    rounds,
+                    hp_left,
+                    winner: race,
+                    elf_died,
+                };
+            }
+            // The weakest adjacent enemy; ties go to the first in reading order
+            let target = |units: &[Unit], pos: Pos| {
+                units
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, u)| u.hp > 0 && u.race != race && neighbours(pos).contains(&u.pos))
+                    .min_by_key(|(_, u)| (u.hp, u.pos))
+                    .map(|(j, _)| j)
+            };
+            if target(&units, units[i].pos).is_none() {
+                if let Some(step) = choose_step(cave, &units, &units[i]) {
+                    units[i].pos = step;
+                }
+            }
+            if let Some(j) = target(&units, units[i].pos) {
+                attacked[j] = true;
+                units[j].hp -= match race {
+                    Race::Elf => elf_attack,
+                    Race::Goblin => ATTACK_POWER,
+                };
+                if units[j].hp <= 0 && units[j].race == Race::Elf {
+                    elf_died = true;
+                    if stop_if_elf_dies {
+                        return Outcome {
+                            rounds,
+                            hp_left: 0,
+                            winner: Race::Goblin,
+                            elf_died,
+                        };
+                    }
+                }
+            }
+        }
+        rounds += 1;
+        if replay {
+            let caption = format!(
+                "After {} round{}:",
+                rounds,
+                if rounds == 1 { "" } else { "s" }
+            );
+            animate::frame(&caption, &render(cave, &units, &attacked));
+        }
+        units.retain(|unit| unit.hp > 0);
+    }
+}
+
+fn do_part1(data: &str) -> i32 {
+    let (cave, units) = parse(data);
+    let outcome = combat(
+        &cave,
+        units,
+        ATTACK_POWER,
+        false,
+        options::enabled("replay"),
+    );
+    let winners = match outcome.winner {
+        Race::Elf => "elves",
+        Race::Goblin => "goblins",
+    };
+    explain!(
+        "The {} win after {} full rounds with {} hit points left",
+        winners,
+        outcome.rounds,
+        outcome.hp_left
+    );
+    outcome.score()
+}
+
+pub fn part1() -> Result<(), InputError> {
+    let data = read_input(15)?;
+    let answer = do_part1(&data);
+    stopwatch::lap("solve");
+    println!("Day 15 (Part 1): {}", answer);
+    Ok(())
+}
+
+fn do_part2(data: &str) -> i32 {
+    let (cave, units) = parse(data);
+    // More attack power doesn't always mean fewer deaths (the timing of kills changes who
+    // moves where), so try each power in turn rather than searching (a few at once, on their
+    // own threads)
+    let (attack, outcome) = executor::first(ATTACK_POWER as usize + 1, |attack| {
+        Some(combat(&cave, units.clone(), attack as i32, true, false)).filter(|o| !o.elf_died)
+    });
+    let attack = attack as i32;
+    explain!("The elves need an attack power of {}", attack);
+    if options::enabled("replay") {
+        combat(&cave, units, attack, true, true);
+    }
+    outcome.score()
+}
+
+pub fn part2() -> Result<(), InputError> {
+    let data = read_input(15)?;
+    let answer = do_part2(&data);
+    stopwatch::lap("solve");
+    println!("Day 15 (Part 2): {}", answer);
+    Ok(())
+}
+
+#[test]
+fn test_movement() {
+    // From the puzzle text: the elf heads for the square to the right of the goblin below it
+    let (cave, units) = parse("#######\n#E..G.#\n#...#.#\n#.G.#G#\n#######\n");
+    assert_eq!(choose_step(&cave, &units, &units[0]), Some((1, 2)));
+    let (cave, units) = parse("#######\n#.E...#\n#.....#\n#...G.#\n#######\n");
+    assert_eq!(choose_step(&cave, &units, &units[0]), Some((1, 3)));
+}
+
+#[test]
+fn test_combat() {
+    let (cave, units) = parse(EXAMPLES[0]);
+    assert_eq!(
+        combat(&cave, units, ATTACK_POWER, false, false),
+        Outcome {
+            rounds: 47,
+            hp_left: 590,
+            winner: Race::Goblin,
+            elf_died: true,
+        }
+    );
+}
+
+#[test]
+fn test_examples() {
+    let part1: Vec<i32> = EXAMPLES.iter().map(|example| do_part1(example)).collect();
+    assert_eq!(part1, vec![27730, 36334, 39514, 27755, 28944, 18740]);
+    // The second example isn't used in part 2
+    let part2: Vec<i32> = [0, 2, 3, 4, 5]
+        .iter()
+        .map(|&i| do_part2(EXAMPLES[i]))
+        .collect();
+    assert_eq!(part2, vec![4988, 31284, 3478, 6474, 1140]);
+}
+
+#[test]
+fn test_render() {
+    let (cave, mut units) = parse(EXAMPLES[0]);
+    assert_eq!(
+        render(&cave, &units, &[]),
+        concat!(
+            "#######\n",
+            "#.G...#   G(200)\n",
+            "#...EG#   E(200), G(200)\n",
+            "#.#.#G#   G(200)\n",
+            "#..G#E#   G(200), E(200)\n",
+            "#.....#\n",
+            "#######\n",
+        )
+    );
+    // The elf at the bottom right kills the goblin above it, and another goblin is hit
+    units[3].hp = 0;
+    units[2].hp = 197;
+    assert_eq!(
+        render(&cave, &units, &[false, false, true, true])
+            .lines()
+            .nth(2),
+        Some("#...Eg#   E(200), G(197)")
+    );
+    assert_eq!(render(&cave, &units, &[]).lines().nth(3), Some("#.#.#x#"));
+}
@@ -0,0 +1,193 @@
+//! Problem: https://adventofcode.com/2018/day/16
+//! The device's manual has samples of instructions (with numbered opcodes) and the registers
+//! before and after each one, followed by a test program
+//! eg. Before: [3, 2, 1, 1]
+//! 9 2 1 2
+//! After:  [3, 2, 2, 1]
+//! Part 1: how many samples behave like three or more opcodes
+//! Part 2: work out which number is which opcode, run the test program, and report register 0
+//!
+//! `--explain` prints the opcode table part 2 works out, for checking elfcode by hand (days 19
+//! and 21)
+
+use crate::elfcode::Op;
+use crate::input::{read_input, InputError};
+use crate::stopwatch;
+use std::collections::BTreeSet;
+
+/// The example from the puzzle text
+pub const EXAMPLES: &[&str] = &[include_str!("../data/examples/day16-1.txt")];
+
+/// An instruction as it appears in the manual: the opcode is still just a number
+type RawInstruction = [usize; 4];
+
+/// One instruction, and what it did to the registers
+#[derive(Debug, PartialEq)]
+pub struct Sample {
+    before: [usize; 4],
+    instruction: RawInstruction,
+    after: [usize; 4],
+}
+
+impl Sample {
+    /// Returns the opcodes that would have the same effect as the sample
+    fn matches(&self) -> BTreeSet<Op> {
+        let [_, a, b, c] = self.instruction;
+        Op::ALL
+            .iter()
+            .filter(|op| {
+                let mut registers = self.before;
+                op.apply(a, b, c, &mut registers).is_some() && registers == self.after
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+/// Returns the first four numbers in a line (ignoring everything that isn't a digit)
+fn four_numbers(line: &str) -> Result<[usize; 4], String> {
+    let numbers: Vec<usize> = line
+        .split(|ch: char| !ch.is_ascii_digit())
+        .filter_map(|n| n.parse().ok())
+        .collect();
+    match numbers[..] {
+        [a, b, c, d] => Ok([a, b, c, d]),
+        _ => Err(format!("Expected four numbers: {}", line)),
+    }
+}
+
+/// Reads the samples and the test program
+fn parse(data: &str) -> Result<(Vec<Sample>, Vec<RawInstruction>), String> {
+    let mut samples = Vec::new();
+    let mut program = Vec::new();
+    let mut lines = data.lines().filter(|line| !line.is_empty());
+    while let Some(line) = lines.next() {
+        if line.starts_with("Before:") {
+            let mut next = || {
+                lines
+                    .next()
+                    .ok_or("The manual ends part way through a sample")
+            };
+            samples.push(Sample {
+                before: four_numbers(line)?,
+                instruction: four_numbers(next()?)?,
+                after: four_numbers(next()?)?,
+            });
+        } else {
+            program.push(four_numbers(line)?);
+        }
+    }
+    Ok((samples, program))
+}
+
+fn do_part1(samples: &[Sample]) -> usize {
+    samples
+        .iter()
+        .filter(|sample| sample.matches().len() >= 3)
+        .count()
+}
+
+pub fn part1() -> Result<(), InputError> {
+    let (samples, _) = parse(&read_input(16)?).unwrap();
+    stopwatch::lap("parse");
+    let answer = do_part1(&samples);
+    stopwatch::lap("solve");
+    println!("Day 16 (Part 1): {}", answer);
+    Ok(())
+}
+
+/// Works out which opcode each number stands for
+/// Each number starts out able to be any opcode the samples agree with. Whenever a number is down
+/// to a single opcode, no other number can be that opcode, which usually pins down another
+/// Returns the opcode for each number (so `ops[9]` is what 9 means)
+pub fn deduce(samples: &[Sample]) -> Result<Vec<Op>, String> {
+    let mut possible: Vec<BTreeSet<Op>> = vec![Op::ALL.iter().cloned().collect(); 16];
+    for sample in samples {
+        let number = sample.instruction[0];
+        let candidates = possible
+            .get_mut(number)
+            .ok_or_else(|| format!("There's no opcode number {}", number))?;
+        *candidates = &*candidates & &sample.matches();
+    }
+    let mut known: Vec<Option<Op>> = vec![None; 16];
+    while let Some(number) = (0..16).find(|&n| known[n].is_none() && possible[n].len() == 1) {
+        let op = *possible[number].iter().next().unwrap();
+        known[number] = Some(op);
+        possible.iter_mut().for_each(|ops| {
+            ops.remove(&op);
+        });
+    }
+    known
+        .into_iter()
+        .enumerate()
+        .map(|(number, op)| op.ok_or_else(|| format!("Unable to work out opcode {}", number)))
+        .collect()
+}
+
+/// Returns the opcode table, and register 0 after running the test program with it
+fn do_part2(samples: &[Sample], program: &[RawInstruction]) -> Result<(Vec<Op>, usize), String> {
+    let ops = deduce(samples)?;
+    let mut registers = [0; 4];
+    for &[number, a, b, c] in program {
+        ops[number]
+            .apply(a, b, c, &mut registers)
+            .ok_or_else(|| format!("Bad register in {} {} {} {}", number, a, b, c))?;
+    }
+    Ok((ops, registers[0]))
+}
+
+pub fn part2() -> Result<(), InputError> {
+    let (samples, program) = parse(&read_input(16)?).unwrap();
+    stopwatch::lap("parse");
+    let answer = do_part2(&samples, &program);
+    stopwatch::lap("solve");
+    match answer {
+        Ok((ops, answer)) => {
+            for (number, op) in ops.iter().enumerate() {
+                explain!("Opcode {:>2} is {}", number, op.name());
+            }
+            println!("Day 16 (Part 2): {}", answer)
+        }
+        Err(err) => println!("Day 16 (Part 2): UNKNOWN ({})", err),
+    }
+    Ok(())
+}
+
+#[test]
+fn test_example() {
+    let (samples, program) = parse(EXAMPLES[0]).unwrap();
+    assert!(program.is_empty());
+    let ops: Vec<Op> = samples[0].matches().into_iter().collect();
+    assert_eq!(ops, vec![Op::Addi, Op::Mulr, Op::Seti]);
+    assert_eq!(do_part1(&samples), 1);
+    // One sample isn't enough to work out every opcode
+    assert!(deduce(&samples).is_err());
+}
+
+#[test]
+fn test_deduce() {
+    // Make up some random samples for each opcode, numbering the opcodes in reverse order
+    let mut rng = crate::generate::Rng::new(16);
+    let mut samples = Vec::new();
+    for (i, op) in Op::ALL.iter().enumerate() {
+        for _ in 0..20 {
+            let mut random = || rng.range(0, 3);
+            let before = [random(), random(), random(), random()];
+            let (a, b, c) = (random(), random(), random());
+            let mut after = before;
+            op.apply(a, b, c, &mut after).unwrap();
+            samples.push(Sample {
+                before,
+                instruction: [15 - i, a, b, c],
+                after,
+            });
+        }
+    }
+    let mut reversed = Op::ALL.to_vec();
+    reversed.reverse();
+    assert_eq!(deduce(&samples).as_ref(), Ok(&reversed));
+    // Part 2 hands back the same table, along with what the program left in register 0
+    // (seti and addi are 9 and 1 in the usual order, so 6 and 14 here)
+    let program = [[6, 7, 0, 0], [14, 0, 5, 0]];
+    assert_eq!(do_part2(&samples, &program), Ok((reversed, 12)));
+}
@@ -0,0 +1,349 @@
+//! Problem: https://adventofcode.com/2018/day/17
+//! Water falls from a spring at x=500, y=0 into ground scanned as veins of clay. It fills any
+//! clay basins it lands in, and spills over their sides
+//! eg. x=495, y=2..7
+//! y=7, x=495..501
+//! Part 1: how many squares the water reaches (between the highest and lowest clay)
+//! Part 2: how many squares hold water once the spring stops
+//!
+//! `--set render` draws the ground once the water has settled, with | for flowing water and ~ for
+//! still water, as the puzzle text does
+//! `--set render=N` also shows the water as it goes, every N steps of the fill: the rows around
+//! where the water has got to
+
+use crate::animate;
+use crate::input::{read_input, InputError};
+use crate::options;
+use crate::stopwatch;
+use std::ops::Range;
+use std::str::FromStr;
+
+/// The example from the puzzle text
+pub const EXAMPLES: &[&str] = &[include_str!("../data/examples/day17-1.txt")];
+
+/// Where the spring is
+const SPRING_X: usize = 500;
+
+/// How many rows either side of the water `--set render=N` shows in each frame
+const FRAME_ROWS: usize = 20;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Square {
+    Sand,
+    Clay,
+    /// Water passing through
+    Flowing,
+    /// Water sitting in a basin
+    Still,
+}
+
+/// A vertical or horizontal line of clay
+#[derive(Debug, PartialEq)]
+struct Vein {
+    xs: (usize, usize),
+    ys: (usize, usize),
+}
+
+impl FromStr for Vein {
+    type Err = String;
+
+    /// eg. x=495, y=2..7 or y=7, x=495..501
+    fn from_str(s: &str) -> Result<Vein, String> {
+        let error = || format!("Unable to parse vein: {}", s);
+        let range = |text: &str| -> Result<(usize, usize), String> {
+            let numbers = text
+                .split("..")
+                .map(|n| n.trim().parse::<usize>())
+                .collect::<Result<Vec<usize>, _>>()
+                .map_err(|_| error())?;
+            match numbers[..] {
+                [n] => Ok((n, n)),
+                [low, high] => Ok((low, high)),
+                _ => Err(error()),
+            }
+        };
+        let (first, second) = s.split_once(", ").ok_or_else(error)?;
+        let (first_axis, first) = first.split_once('=').ok_or_else(error)?;
+        let (_, second) = second.split_once('=').ok_or_else(error)?;
+        let (first, second) = (range(first)?, range(second)?);
+        match first_axis {
+            "x" => Ok(Vein {
+                xs: first,
+                ys: second,
+            }),
+            "y" => Ok(Vein {
+                xs: second,
+                ys: first,
+            }),
+            _ => Err(error()),
+        }
+    }
+}
+
+/// Work still to do while following the water
+enum Task {
+    /// Water falls from x,y until it lands on something
+    Fall(usize, usize),
+    /// Water has landed at x,y and spreads sideways
+    Spread(usize, usize),
+}
+
+/// A scan of the ground around the spring
+struct Ground {
+    squares: Vec<Vec<Square>>,
+    /// The x of the first column in `squares`
+    left: usize,
+    /// The rows between the highest and lowest clay, which are the only ones counted
+    min_y: usize,
+    max_y: usize,
+}
+
+impl FromStr for Ground {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Ground, String> {
+        let veins = s
+            .lines()
+            .map(|line| line.parse::<Vein>())
+            .collect::<Result<Vec<Vein>, String>>()?;
+        let min_y = veins.iter().map(|v| v.ys.0).min().ok_or("No clay")?;
+        let max_y = veins.iter().map(|v| v.ys.1).max().unwrap();
+        // Leave a column either side of the clay for water to fall down
+        let left = veins.iter().map(|v| v.xs.0).min().unwrap().min(SPRING_X) - 1;
+        let right = veins.iter().map(|v| v.xs.1).max().unwrap().max(SPRING_X) + 1;
+        let mut squares = vec![vec![Square::Sand; right - left + 1]; max_y + 1];
+        for vein in &veins {
+            for row in &mut squares[vein.ys.0..=vein.ys.1] {
+                row[vein.xs.0 - left..=vein.xs.1 - left]
+                    .iter_mut()
+                    .for_each(|square| *square = Square::Clay);
+            }
+        }
+        Ok(Ground {
+            squares,
+            left,
+            min_y,
+            max_y,
+        })
+    }
+}
+
+impl Ground {
+    fn at(&self, x: usize, y: usize) -> Square {
+        self.squares[y][x - self.left]
+    }
+
+    fn set(&mut self, x: usize, y: usize, square: Square) {
+        self.squares[y][x - self.left] = square;
+    }
+
+    /// Returns true if water at x,y has something to sit on
+    fn supported(&self, x: usize, y: usize) -> bool {
+        matches!(self.at(x, y + 1), Square::Clay | Square::Still)
+    }
+
+    /// Returns how far water at x,y spreads in direction `dx`, and whether it hits a wall (rather
+    /// than running off an edge)
+    fn reach(&self, x: usize, y: usize, dx: isize) -> (usize, bool) {
+        let mut x = x;
+        loop {
+            if !self.supported(x, y) {
+                return (x, false);
+            }
+            let next = (x as isize + dx) as usize;
+            if self.at(next, y) == Square::Clay {
+                return (x, true);
+            }
+            x = next;
+        }
+    }
+
+    /// Follows the water from the spring until it has gone everywhere it can
+    /// Uses a worklist rather than recursion, as real inputs are deep enough to overflow the stack
+    /// If `frame_every` is given, the ground is shown every that many tasks
+    fn flow(&mut self, frame_every: Option<usize>) {
+        let mut tasks = vec![Task::Fall(SPRING_X, 0)];
+        let mut done = 0;
+        while let Some(task) = tasks.pop() {
+            if let Some(every) = frame_every.filter(|&every| done % every.max(1) == 0) {
+                let (Task::Fall(x, y) | Task::Spread(x, y)) = task;
+                let rows = y.saturating_sub(FRAME_ROWS)..(y + FRAME_ROWS).min(self.max_y + 1);
+                let caption = format!("Step {} (every {}): water at {},{}", done, every, x, y);
+                animate::frame(&caption, &self.render_rows(rows));
+            }
+            done += 1;
+            match task {
+                Task::Fall(x, mut y) => {
+                    self.set(x, y, Square::Flowing);
+                    while y < self.max_y && self.at(x, y + 1) == Square::Sand {
+                        y += 1;
+                        self.set(x, y, Square::Flowing);
+                    }
+                    // Water that reaches the bottom, or joins water that's already flowing, has
+                    // already been dealt with
+                    if y < self.max_y && self.supported(x, y) {
+                        tasks.push(Task::Spread(x, y));
+                    }
+                }
+                Task::Spread(x, y) => {
+                    if self.at(x, y) == Square::Still || !self.supported(x, y) {
+                        continue;
+                    }
+                    let (left, left_wall) = self.reach(x, y, -1);
+                    let (right, right_wall) = self.reach(x, y, 1);
+                    if left_wall && right_wall {
+                        // The water fills this row of the basin, so any water above it spreads too
+                        for x in left..=right {
+                            self.set(x, y, Square::Still);
+                            if y > 0 && self.at(x, y - 1) == Square::Flowing {
+                                tasks.push(Task::Spread(x, y - 1));
+                            }
+                        }
+                    } else {
+                        for x in left..=right {
+                            self.set(x, y, Square::Flowing);
+                        }
+                        if !left_wall {
+                            tasks.push(Task::Fall(left, y));
+                        }
+                        if !right_wall {
+                            tasks.push(Task::Fall(right, y));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns how many squares (in the scanned rows) `wet` returns true for
+    fn count(&self, wet: impl Fn(Square) -> bool) -> usize {
+        self.squares[self.min_y..=self.max_y]
+            .iter()
+            .flatten()
+            .filter(|&&square| wet(square))
+            .count()
+    }
+
+    /// Draws the ground the way the puzzle text does
+    fn render(&self) -> String {
+        self.render_rows(0..self.squares.len())
+    }
+
+    /// Draws just `rows` of the ground
+    fn render_rows(&self, rows: Range<usize>) -> String {
+        self.squares[rows]
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|square| match square {
+                        Square::Sand => '.',
+                        Square::Clay => '#',
+                        Square::Flowing => '|',
+                        Square::Still => '~',
+                    })
+                    .chain(Some('\n'))
+                    .collect::<String>()
+            })
+            .collect()
+    }
+}
+
+/// How often `--set render=N` asks for a frame while the water flows
+fn frame_every() -> Option<usize> {
+    options::get("render").and_then(|every| every.parse().ok())
+}
+
+pub fn part1() -> Result<(), InputError> {
+    let mut ground: Ground = read_input(17)?.parse().unwrap();
+    stopwatch::lap("parse");
+    ground.flow(frame_every());
+    let answer = ground.count(|square| matches!(square, Square::Flowing | Square::Still));
+    stopwatch::lap("solve");
+    println!("Day 17 (Part 1): {}", answer);
+    Ok(())
+}
+
+pub fn part2() -> Result<(), InputError> {
+    let mut ground: Ground = read_input(17)?.parse().unwrap();
+    stopwatch::lap("parse");
+    ground.flow(None);
+    let answer = ground.count(|square| square == Square::Still);
+    stopwatch::lap("solve");
+    println!("Day 17 (Part 2): {}", answer);
+    if options::enabled("render") {
+        print!("{}", ground.render());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_parse_vein() {
+    assert_eq!(
+        "y=7, x=495..501".parse::<Vein>(),
+        Ok(Vein {
+            xs: (495, 501),
+            ys: (7, 7)
+        })
+    );
+    assert!("z=7, x=495..501".parse::<Vein>().is_err());
+}
+
+#[test]
+fn test_example() {
+    let mut ground: Ground = EXAMPLES[0].parse().unwrap();
+    ground.flow(None);
+    assert_eq!(
+        ground.render(),
+        concat!(
+            "......|.......\n",
+            "......|.....#.\n",
+            ".#..#||||...#.\n",
+            ".#..#~~#|.....\n",
+            ".#..#~~#|.....\n",
+            ".#~~~~~#|.....\n",
+            ".#~~~~~#|.....\n",
+            ".#######|.....\n",
+            "........|.....\n",
+            "...|||||||||..\n",
+            "...|#~~~~~#|..\n",
+            "...|#~~~~~#|..\n",
+            "...|#~~~~~#|..\n",
+            "...|#######|..\n",
+        )
+    );
+    assert_eq!(
+        ground.count(|s| matches!(s, Square::Flowing | Square::Still)),
+        57
+    );
+    assert_eq!(ground.count(|s| s == Square::Still), 29);
+}
+
+#[test]
+fn test_basin_overflows_into_wider_basin() {
+    // The water lands on a ledge inside a bigger basin, whose sides it has to fill up to
+    let mut ground: Ground = "x=495, y=2..8\ny=8, x=495..505\nx=505, y=2..8\ny=5, x=499..501"
+        .parse()
+        .unwrap();
+    ground.flow(None);
+    // Everything inside the big basin fills, up to its rim
+    assert_eq!(ground.count(|s| s == Square::Still), 9 * 6 - 3);
+}
+
+#[test]
+fn test_basin_under_the_spring() {
+    // The spring sits in a basin, so the water fills the top row and there's no row above it
+    let mut ground: Ground = "y=1, x=498..502\nx=498, y=0..1\nx=502, y=0..1"
+        .parse()
+        .unwrap();
+    ground.flow(None);
+    assert_eq!(ground.render(), ".#~~~#.\n.#####.\n");
+}
+
+#[test]
+fn test_flow_frames() {
+    // Showing frames doesn't change where the water goes
+    let mut ground: Ground = EXAMPLES[0].parse().unwrap();
+    ground.flow(Some(5));
+    assert_eq!(ground.count(|s| s == Square::Still), 29);
+    assert_eq!(ground.render_rows(0..2), "......|.......\n......|.....#.\n");
+}
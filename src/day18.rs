@@ -0,0 +1,215 @@
+//! Problem: https://adventofcode.com/2018/day/18
+//! Each acre of the lumber collection area is open ground (.), trees (|) or a lumberyard (#), and
+//! changes every minute depending on its eight neighbours
+//! Part 1: the resource value (wooded acres times lumberyards) after 10 minutes
+//! Part 2: the resource value after 1,000,000,000 minutes
+//!
+//! `--set live` shows the area every minute with its resource value, up to the minute that
+//! repeats an earlier one (which is where the cycle is found)
+
+use crate::animate;
+use crate::input::{read_input, InputError};
+use crate::options;
+use crate::stopwatch;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// The example from the puzzle text
+pub const EXAMPLES: &[&str] = &[include_str!("../data/examples/day18-1.txt")];
+
+/// How many minutes part 2 asks about
+const LONG_TIME: usize = 1_000_000_000;
+
+/// Returns the state after `n` steps from `start`
+/// Every state is remembered, so once one repeats we know the cycle length and can skip straight
+/// to the end instead of stepping through every cycle
+/// `watch` is given every state worked out, with its step number, and the step it repeats if it's
+/// the one that closes the cycle
+fn after<T: Clone + Eq + Hash>(
+    start: T,
+    n: usize,
+    step: impl Fn(&T) -> T,
+    mut watch: impl FnMut(usize, &T, Option<usize>),
+) -> T {
+    let mut seen: HashMap<T, usize> = HashMap::new();
+    let mut history: Vec<T> = Vec::new();
+    let mut state = start;
+    for i in 0..n {
+        if let Some(&first) = seen.get(&state) {
+            watch(i, &state, Some(first));
+            let cycle = i - first;
+            explain!(
+                "Minute {} repeats minute {} (a cycle of {})",
+                i,
+                first,
+                cycle
+            );
+            return history[first + (n - first) % cycle].clone();
+        }
+        watch(i, &state, None);
+        seen.insert(state.clone(), i);
+        history.push(state.clone());
+        state = step(&state);
+    }
+    watch(n, &state, None);
+    state
+}
+
+/// The lumber collection area, one byte per acre
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct Area {
+    rows: Vec<Vec<u8>>,
+}
+
+impl Area {
+    fn parse(data: &str) -> Area {
+        Area {
+            rows: data.lines().map(|line| line.bytes().collect()).collect(),
+        }
+    }
+
+    /// Counts the acres of `kind` around x,y
+    fn neighbours(&self, x: usize, y: usize, kind: u8) -> usize {
+        let rows = y.saturating_sub(1)..=(y + 1).min(self.rows.len() - 1);
+        rows.flat_map(|ny| {
+            let columns = x.saturating_sub(1)..=(x + 1).min(self.rows[ny].len() - 1);
+            columns.map(move |nx| (nx, ny))
+        })
+        .filter(|&(nx, ny)| (nx, ny) != (x, y) && self.rows[ny][nx] == kind)
+        .count()
+    }
+
+    /// Returns the area a minute later
+    fn step(&self) -> Area {
+        let rows = self
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .map(|(x, &acre)| match acre {
+                        b'.' if self.neighbours(x, y, b'|') >= 3 => b'|',
+                        b'|' if self.neighbours(x, y, b'#') >= 3 => b'#',
+                        b'#' if self.neighbours(x, y, b'#') == 0
+                            || self.neighbours(x, y, b'|') == 0 =>
+                        {
+                            b'.'
+                        }
+                        _ => acre,
+                    })
+                    .collect()
+            })
+            .collect();
+        Area { rows }
+    }
+
+    /// The number of wooded acres times the number of lumberyards
+    fn resource_value(&self) -> usize {
+        let count = |kind: u8| {
+            self.rows
+                .iter()
+                .flatten()
+                .filter(|&&acre| acre == kind)
+                .count()
+        };
+        count(b'|') * count(b'#')
+    }
+
+    /// Draws the area the way it was read in
+    fn render(&self) -> String {
+        self.rows
+            .iter()
+            .map(|row| String::from_utf8_lossy(row).to_string() + "\n")
+            .collect()
+    }
+}
+
+/// Shows a minute of the area for `--set live`, calling out the minute that closes the cycle
+fn show(minute: usize, area: &Area, repeats: Option<usize>) {
+    let mut caption = format!(
+        "Minute {}: resource value {}",
+        minute,
+        area.resource_value()
+    );
+    if let Some(first) = repeats {
+        caption += &format!(
+            " <- the same as minute {}, so it repeats every {} minute{}",
+            first,
+            minute - first,
+            if minute - first == 1 { "" } else { "s" }
+        );
+    }
+    animate::frame(&caption, &area.render());
+}
+
+/// Returns the area after `minutes`, shown as it goes if `--set live` was given
+fn run(area: Area, minutes: usize) -> Area {
+    let live = options::enabled("live");
+    after(area, minutes, Area::step, |minute, area, repeats| {
+        if live {
+            show(minute, area, repeats)
+        }
+    })
+}
+
+pub fn part1() -> Result<(), InputError> {
+    let area = Area::parse(&read_input(18)?);
+    let answer = run(area, 10).resource_value();
+    stopwatch::lap("solve");
+    println!("Day 18 (Part 1): {}", answer);
+    Ok(())
+}
+
+pub fn part2() -> Result<(), InputError> {
+    let area = Area::parse(&read_input(18)?);
+    let answer = run(area, LONG_TIME).resource_value();
+    stopwatch::lap("solve");
+    println!("Day 18 (Part 2): {}", answer);
+    Ok(())
+}
+
+#[test]
+fn test_step() {
+    let area = Area::parse(EXAMPLES[0]).step();
+    let expected = Area::parse(concat!(
+        ".......##.\n",
+        "......|###\n",
+        ".|..|...#.\n",
+        "..|#||...#\n",
+        "..##||.|#|\n",
+        "...#||||..\n",
+        "||...|||..\n",
+        "|||||.||.|\n",
+        "||||||||||\n",
+        "....||..|.\n",
+    ));
+    assert_eq!(area, expected);
+}
+
+#[test]
+fn test_example() {
+    let area = Area::parse(EXAMPLES[0]);
+    assert_eq!(area.render(), EXAMPLES[0].replace("\r\n", "\n"));
+    assert_eq!(run(area, 10).resource_value(), 1147);
+}
+
+#[test]
+fn test_after_skips_cycles() {
+    // A sequence that falls into a cycle after a few steps
+    let step = |x: &u64| (x * x + 1) % 1009;
+    let mut brute_force = 2;
+    for _ in 0..5000 {
+        brute_force = step(&brute_force);
+    }
+    let mut watched = Vec::new();
+    assert_eq!(
+        after(2, 5000, step, |i, _, repeats| watched.push((i, repeats))),
+        brute_force
+    );
+    // Every step up to the one that closes the cycle is watched, and nothing after it
+    let (last, first) = watched.pop().unwrap();
+    assert_eq!(watched.len(), last);
+    assert!(first.unwrap() < last);
+    assert!(watched.iter().all(|(_, repeats)| repeats.is_none()));
+}
@@ -0,0 +1,150 @@
+//! Problem: https://adventofcode.com/2018/day/19
+//! Run a program on the device, with its instruction pointer bound to a register
+//! eg. #ip 0
+//! seti 5 0 1
+//! Part 1: register 0 when the program halts
+//! Part 2: the same, when register 0 starts at 1
+//!
+//! The real inputs add up the divisors of a number the program works out first, using a pair of
+//! nested loops. With register 0 set that number is in the millions, so running the loops would
+//! take years; instead we run the program until it works the number out, and add up the
+//! divisors ourselves
+
+use crate::elfcode::{Program, Vm};
+use crate::input::{read_input, InputError};
+use crate::stopwatch;
+
+/// The example from the puzzle text
+pub const EXAMPLES: &[&str] = &[include_str!("../data/examples/day19-1.txt")];
+
+/// Runs the program to the end, returning register 0
+fn run(program: &Program, register0: usize) -> usize {
+    let mut vm = Vm::new(program, [register0, 0, 0, 0, 0, 0]);
+    vm.run();
+    vm.registers[0]
+}
+
+/// Runs the program until it's worked out the number whose divisors it's going to add up, and
+/// returns that number
+/// The programs start by jumping to the set up code at the end, which works the number out and
+/// jumps back to instruction 1 to start the loops. By then it's the biggest number in the
+/// registers. Returns None if the program isn't laid out like that
+fn divisor_target(program: &Program, register0: usize) -> Option<usize> {
+    let mut vm = Vm::new(program, [register0, 0, 0, 0, 0, 0]);
+    if !vm.step() || vm.ip == 1 {
+        return None;
+    }
+    while vm.ip != 1 {
+        if !vm.step() {
+            return None;
+        }
+    }
+    vm.registers.iter().max().cloned()
+}
+
+/// Returns the sum of every number that divides `n`
+fn sum_of_divisors(n: usize) -> usize {
+    (1..)
+        .take_while(|i| i * i <= n)
+        .filter(|i| n.is_multiple_of(*i))
+        .map(|i| if i * i == n { i } else { i + n / i })
+        .sum()
+}
+
+/// Works out what the program would leave in register 0, without running its loops
+/// Falls back to running the program if it doesn't look like it's adding up divisors
+fn shortcut(program: &Program, register0: usize) -> usize {
+    match divisor_target(program, register0) {
+        Some(target) => {
+            explain!("The program adds up the divisors of {}", target);
+            sum_of_divisors(target)
+        }
+        None => run(program, register0),
+    }
+}
+
+pub fn part1() -> Result<(), InputError> {
+    let program: Program = read_input(19)?.parse().unwrap();
+    stopwatch::lap("parse");
+    let answer = run(&program, 0);
+    stopwatch::lap("solve");
+    println!("Day 19 (Part 1): {}", answer);
+    Ok(())
+}
+
+pub fn part2() -> Result<(), InputError> {
+    let program: Program = read_input(19)?.parse().unwrap();
+    stopwatch::lap("parse");
+    let answer = shortcut(&program, 1);
+    stopwatch::lap("solve");
+    println!("Day 19 (Part 2): {}", answer);
+    Ok(())
+}
+
+/// Checks that the shortcut gives the same answer as actually running the program, for part 1
+pub fn verify_invariants() -> Result<(), String> {
+    let program: Program = read_input(19).map_err(|err| err.to_string())?.parse()?;
+    let (ran, shortcut) = (run(&program, 0), shortcut(&program, 0));
+    if ran == shortcut {
+        Ok(())
+    } else {
+        Err(format!(
+            "running the program gives {}, but the divisor shortcut gives {}",
+            ran, shortcut
+        ))
+    }
+}
+
+/// A program shaped like the real inputs: it adds up the divisors of 36, or of 1000 if register 0
+/// starts at 1
+#[cfg(test)]
+const DIVISOR_PROGRAM: &str = "#ip 3
+addi 3 16 3
+seti 1 0 4
+seti 1 7 2
+mulr 4 2 1
+eqrr 1 5 1
+addr 1 3 3
+addi 3 1 3
+addr 4 0 0
+addi 2 1 2
+gtrr 2 5 1
+addr 3 1 3
+seti 2 3 3
+addi 4 1 4
+gtrr 4 5 1
+addr 1 3 3
+seti 1 6 3
+mulr 3 3 3
+seti 36 0 5
+addr 3 0 3
+seti 0 0 3
+seti 1000 0 5
+seti 0 0 0
+seti 0 0 3";
+
+#[test]
+fn test_example() {
+    let program: Program = EXAMPLES[0].parse().unwrap();
+    assert_eq!(run(&program, 0), 6);
+    // The example doesn't add up divisors: it starts at instruction 1 rather than jumping to it
+    assert_eq!(divisor_target(&program, 0), None);
+    assert_eq!(shortcut(&program, 0), 6);
+}
+
+#[test]
+fn test_sum_of_divisors() {
+    assert_eq!(sum_of_divisors(1), 1);
+    assert_eq!(sum_of_divisors(36), 1 + 2 + 3 + 4 + 6 + 9 + 12 + 18 + 36);
+    assert_eq!(sum_of_divisors(13), 14);
+}
+
+#[test]
+fn test_shortcut() {
+    let program: Program = DIVISOR_PROGRAM.parse().unwrap();
+    assert_eq!(divisor_target(&program, 0), Some(36));
+    assert_eq!(run(&program, 0), 91);
+    assert_eq!(shortcut(&program, 0), 91);
+    assert_eq!(divisor_target(&program, 1), Some(1000));
+    assert_eq!(shortcut(&program, 1), 2340);
+}
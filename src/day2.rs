@@ -1,11 +1,19 @@
+//! Problem - https://adventofcode.com/2018/day/2#part2
+//! You have a bunch of boxes with IDs - you have to run a checksum over them
+//! The checksum counts the exact same letter appearing twice and thrice, then
+//! multiplies them together
+
+use crate::input::{read_input, InputError};
+use crate::stopwatch;
 use std::collections::BTreeMap;
-use std::fs::read_to_string;
 use std::iter::Sum;
 use std::ops::Add;
-/// Problem - https://adventofcode.com/2018/day/2#part2
-/// You have a bunch of boxes with IDs - you have to run a checksum over them
-/// The checksum counts the exact same letter appearing twice and thrice, then
-/// multiplies them together
+
+/// The examples from the puzzle text (one for each part)
+pub const EXAMPLES: &[&str] = &[
+    include_str!("../data/examples/day2-1.txt"),
+    include_str!("../data/examples/day2-2.txt"),
+];
 
 /// Each box id will go in a group
 enum BoxIDGroup {
@@ -95,10 +103,12 @@ impl Sum for BoxIDScorer {
 }
 
 /// Part1 - Find the checksum of all the box ids
-pub fn part1() {
-    let data = read_to_string("data/day2.txt").unwrap();
+pub fn part1() -> Result<(), InputError> {
+    let data = read_input(2)?;
     let answer = do_part1(data);
+    stopwatch::lap("solve");
     println!("Day2 (Part 1): Answer: {}", answer);
+    Ok(())
 }
 
 fn do_part1(data: String) -> usize {
@@ -107,9 +117,9 @@ fn do_part1(data: String) -> usize {
         // Split the input into lines
         .lines()
         // Convert each line into a BoxIDGroup
-        .map(|boxid| BoxIDGroup::from(boxid))
+        .map(BoxIDGroup::from)
         // Convert each group into a score
-        .map(|boxid_group| BoxIDScorer::from(boxid_group))
+        .map(BoxIDScorer::from)
         // Sum the scores
         .sum();
     println!(
@@ -121,10 +131,12 @@ fn do_part1(data: String) -> usize {
 }
 
 /// Part 2: find two boxes that differ by excactly one letter in the same place
-pub fn part2() {
-    let data = read_to_string("data/day2.txt").unwrap();
+pub fn part2() -> Result<(), InputError> {
+    let data = read_input(2)?;
     let answer = do_part2(data);
+    stopwatch::lap("solve");
     println!("Day2 (Part 2): Answer: {}", answer);
+    Ok(())
 }
 
 /// Returns the count of letters that are different (in the same position) between two strings
@@ -138,12 +150,20 @@ fn do_part2(data: String) -> String {
         .lines()
         // Compare every line with every other line
         .flat_map(|line1| data.lines().map(move |line2| (line1, line2)))
-        // We only care about lines that differ by exactly one letter
-        .filter(|(line1, line2)| count_different_letters(line1, line2) == 1)
-        // Take the first one
-        .nth(0)
+        // Take the first pair of lines that differ by exactly one letter
+        .find(|(line1, line2)| count_different_letters(line1, line2) == 1)
         // Unwrap the option
         .unwrap();
+    explain!("Box IDs {} and {} are the closest match", pair.0, pair.1);
+    explain!(
+        "They differ at position {}",
+        pair.0
+            .chars()
+            .zip(pair.1.chars())
+            .position(|(a, b)| a != b)
+            .unwrap()
+    );
+
     // We now have a pair of lines that differ by exactly one letter
     // We need to return the chars that are the same
     pair.0
@@ -156,3 +176,10 @@ fn do_part2(data: String) -> String {
         // Turn it into a String
         .collect()
 }
+
+#[test]
+fn test_part2_generated_input() {
+    // Exactly one pair of generated IDs differ by one letter
+    let data = crate::generate::generate(2, 250, 3).unwrap();
+    assert_eq!(do_part2(data).len(), 25);
+}
@@ -0,0 +1,266 @@
+//! Problem: https://adventofcode.com/2018/day/20
+//! The facility is described by a regex of the routes through it, eg. ^ENWWW(NEEE|SSE(EE|N))$
+//! Each letter is a step through a door, and (A|B) means either A or B can be taken
+//! Part 1: the most doors you'd have to pass through to reach any room
+//! Part 2: how many rooms are at least 1000 doors away
+//!
+//! `Map` gives every room's distance, and the doors to take to reach any room, so the routes can
+//! be checked room by room, and `--set map` draws the facility the way the puzzle does
+
+use crate::input::{read_input, InputError};
+use crate::options;
+use crate::stopwatch;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// The examples from the puzzle text
+pub const EXAMPLES: &[&str] = &[
+    include_str!("../data/examples/day20-1.txt"),
+    include_str!("../data/examples/day20-2.txt"),
+    include_str!("../data/examples/day20-3.txt"),
+    include_str!("../data/examples/day20-4.txt"),
+    include_str!("../data/examples/day20-5.txt"),
+];
+
+/// Part 2 counts the rooms at least this many doors away
+const FAR_AWAY: usize = 1000;
+
+/// A room: x grows eastwards and y southwards, and we start at 0,0
+pub type Room = (i32, i32);
+
+/// The four directions, and the way each one moves
+const DIRECTIONS: [(char, Room); 4] =
+    [('N', (0, -1)), ('E', (1, 0)), ('S', (0, 1)), ('W', (-1, 0))];
+
+/// Every room, and the rooms it has doors to
+pub struct Map {
+    doors: HashMap<Room, HashSet<Room>>,
+}
+
+impl Map {
+    /// Follows every route the regex describes, recording the doors it passes through
+    /// A stack of branch groups replaces recursion: each entry holds the rooms the group started
+    /// from, and the rooms its finished alternatives have ended up in
+    pub fn parse(regex: &str) -> Result<Map, String> {
+        let mut doors: HashMap<Room, HashSet<Room>> = HashMap::new();
+        let mut current: HashSet<Room> = Some((0, 0)).into_iter().collect();
+        let mut groups: Vec<(HashSet<Room>, HashSet<Room>)> = Vec::new();
+        for ch in regex
+            .trim()
+            .trim_start_matches('^')
+            .trim_end_matches('$')
+            .chars()
+        {
+            match ch {
+                'N' | 'E' | 'S' | 'W' => {
+                    let (_, (dx, dy)) = DIRECTIONS.iter().find(|(dir, _)| *dir == ch).unwrap();
+                    current = current
+                        .into_iter()
+                        .map(|(x, y)| {
+                            let next = (x + dx, y + dy);
+                            doors.entry((x, y)).or_default().insert(next);
+                            doors.entry(next).or_default().insert((x, y));
+                            next
+                        })
+                        .collect();
+                }
+                '(' => groups.push((current.clone(), HashSet::new())),
+                '|' => {
+                    let (starts, ends) = groups.last_mut().ok_or("'|' outside of a group")?;
+                    ends.extend(current.drain());
+                    current = starts.clone();
+                }
+                ')' => {
+                    let (_, ends) = groups.pop().ok_or("Unmatched ')'")?;
+                    current.extend(ends);
+                }
+                other => return Err(format!("Unexpected '{}' in the regex", other)),
+            }
+        }
+        if !groups.is_empty() {
+            return Err("Unmatched '('".to_string());
+        }
+        Ok(Map { doors })
+    }
+
+    /// Returns how many doors away each room is from the start
+    pub fn distances(&self) -> HashMap<Room, usize> {
+        let mut distances = HashMap::new();
+        distances.insert((0, 0), 0);
+        let mut queue = VecDeque::new();
+        queue.push_back((0, 0));
+        while let Some(room) = queue.pop_front() {
+            let distance = distances[&room];
+            for next in self.doors.get(&room).into_iter().flatten() {
+                if !distances.contains_key(next) {
+                    distances.insert(*next, distance + 1);
+                    queue.push_back(*next);
+                }
+            }
+        }
+        distances
+    }
+
+    /// Returns the furthest room from the start and its distance, taking the first in reading
+    /// order if there's a tie
+    pub fn furthest(distances: &HashMap<Room, usize>) -> (Room, usize) {
+        distances
+            .iter()
+            .map(|(&(x, y), &distance)| (distance, Reverse((y, x))))
+            .max()
+            .map(|(distance, Reverse((y, x)))| ((x, y), distance))
+            .unwrap_or(((0, 0), 0))
+    }
+
+    /// Returns the doors to go through (eg. "WNE") to reach `room` from the start by the
+    /// shortest route, or None if there's no way there
+    /// Works back from `room`, each time stepping to a neighbour one door closer to the start
+    /// (trying N, E, S then W), so it needs the map's `distances`
+    pub fn path_to(&self, distances: &HashMap<Room, usize>, room: Room) -> Option<String> {
+        let mut path = Vec::new();
+        let mut here = room;
+        let mut distance = *distances.get(&here)?;
+        while distance > 0 {
+            let neighbours = self.doors.get(&here)?;
+            let (dir, back) = DIRECTIONS
+                .iter()
+                .map(|&(dir, (dx, dy))| (dir, (here.0 - dx, here.1 - dy)))
+                .find(|(_, back)| {
+                    neighbours.contains(back) && distances.get(back) == Some(&(distance - 1))
+                })?;
+            path.push(dir);
+            here = back;
+            distance -= 1;
+        }
+        Some(path.into_iter().rev().collect())
+    }
+
+    /// Draws the facility the way the puzzle does: # for walls, . for rooms, | and - for doors,
+    /// and X for the room we start in
+    pub fn render(&self) -> String {
+        let xs = self.doors.keys().map(|&(x, _)| x);
+        let ys = self.doors.keys().map(|&(_, y)| y);
+        let (min_x, max_x) = (xs.clone().min().unwrap_or(0), xs.max().unwrap_or(0));
+        let (min_y, max_y) = (ys.clone().min().unwrap_or(0), ys.max().unwrap_or(0));
+        // Each room is a square with a wall or door on each side, shared with its neighbours
+        let width = (max_x - min_x + 1) as usize * 2 + 1;
+        let height = (max_y - min_y + 1) as usize * 2 + 1;
+        let mut grid = vec![vec!['#'; width]; height];
+        for (&(x, y), neighbours) in &self.doors {
+            let (col, row) = ((x - min_x) as usize * 2 + 1, (y - min_y) as usize * 2 + 1);
+            grid[row][col] = if (x, y) == (0, 0) { 'X' } else { '.' };
+            for &(nx, ny) in neighbours {
+                let door_col = (col as i32 + nx - x) as usize;
+                let door_row = (row as i32 + ny - y) as usize;
+                grid[door_row][door_col] = if ny == y { '|' } else { '-' };
+            }
+        }
+        grid.into_iter()
+            .map(|row| row.into_iter().chain(Some('\n')).collect::<String>())
+            .collect()
+    }
+}
+
+fn do_part1(regex: &str) -> usize {
+    let map = Map::parse(regex).unwrap();
+    let distances = map.distances();
+    let (room, distance) = Map::furthest(&distances);
+    explain!(
+        "The furthest room is at {},{}: {}",
+        room.0,
+        room.1,
+        map.path_to(&distances, room).unwrap()
+    );
+    distance
+}
+
+pub fn part1() -> Result<(), InputError> {
+    let data = read_input(20)?;
+    let answer = do_part1(&data);
+    stopwatch::lap("solve");
+    println!("Day 20 (Part 1): {}", answer);
+    if options::enabled("map") {
+        print!("{}", Map::parse(&data).unwrap().render());
+    }
+    Ok(())
+}
+
+fn do_part2(regex: &str, far_away: usize) -> usize {
+    let map = Map::parse(regex).unwrap();
+    let distances = map.distances();
+    explain!("The facility has {} rooms", distances.len());
+    distances.values().filter(|&&d| d >= far_away).count()
+}
+
+pub fn part2() -> Result<(), InputError> {
+    let data = read_input(20)?;
+    let answer = do_part2(&data, FAR_AWAY);
+    stopwatch::lap("solve");
+    println!("Day 20 (Part 2): {}", answer);
+    Ok(())
+}
+
+#[test]
+fn test_examples() {
+    let furthest: Vec<usize> = EXAMPLES.iter().map(|regex| do_part1(regex)).collect();
+    assert_eq!(furthest, vec![3, 10, 18, 23, 31]);
+    // ^WNE$ has rooms 0, 1, 2 and 3 doors away
+    assert_eq!(do_part2(EXAMPLES[0], 2), 2);
+}
+
+#[test]
+fn test_parse_errors() {
+    assert!(Map::parse("^N(E|W$").is_err());
+    assert!(Map::parse("^NE)$").is_err());
+    assert!(Map::parse("^NX$").is_err());
+}
+
+#[test]
+fn test_path_to() {
+    let map = Map::parse(EXAMPLES[1]).unwrap();
+    let distances = map.distances();
+    assert_eq!(Map::furthest(&distances), ((1, 1), 10));
+    assert_eq!(
+        map.path_to(&distances, (1, 1)),
+        Some("ENWWWSSEEE".to_string())
+    );
+    assert_eq!(map.path_to(&distances, (0, 0)), Some(String::new()));
+    assert_eq!(map.path_to(&distances, (50, 50)), None);
+    // Every room's path goes through doors, one door closer to the room at a time
+    for (&room, &distance) in &distances {
+        let path = map.path_to(&distances, room).unwrap();
+        assert_eq!(path.len(), distance);
+        let mut here = (0, 0);
+        for (i, ch) in path.chars().enumerate() {
+            let (_, (dx, dy)) = DIRECTIONS.iter().find(|(dir, _)| *dir == ch).unwrap();
+            let next = (here.0 + dx, here.1 + dy);
+            assert!(map.doors[&here].contains(&next));
+            assert_eq!(distances[&next], i + 1);
+            here = next;
+        }
+        assert_eq!(here, room);
+    }
+}
+
+#[test]
+fn test_render() {
+    let render = |regex: &str| Map::parse(regex).unwrap().render();
+    assert_eq!(
+        render(EXAMPLES[0]),
+        concat!("#####\n", "#.|.#\n", "#-###\n", "#.|X#\n", "#####\n")
+    );
+    assert_eq!(
+        render(EXAMPLES[1]),
+        concat!(
+            "#########\n",
+            "#.|.|.|.#\n",
+            "#-#######\n",
+            "#.|.|.|.#\n",
+            "#-#####-#\n",
+            "#.#.#X|.#\n",
+            "#-#-#####\n",
+            "#.|.|.|.#\n",
+            "#########\n",
+        )
+    );
+}
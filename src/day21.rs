@@ -0,0 +1,415 @@
+//! Problem: https://adventofcode.com/2018/day/21
+//! The activation program only reads register 0 in one place: it compares it with a number it has
+//! worked out, and halts if they're equal. Otherwise it works out another number and tries again
+//! Part 1: the lowest value for register 0 that halts the program after the fewest instructions
+//! Part 2: the lowest value that halts it after the most instructions
+//!
+//! Rather than trying values of register 0, we watch the comparison: the first number it's
+//! compared with halts soonest, and the numbers eventually repeat, so the last one before they
+//! do halts last
+//!
+//! Running the program is slow for part 2: on a real input it runs about 2.5 billion
+//! instructions before the numbers repeat (around 20 seconds in a release build). So, like day
+//! 19's `divisor_target`, we decompile the loop that works each number out from the last one
+//! (see `Hash`) and run that directly instead, falling back to the VM if the program doesn't
+//! look like the real ones. `--set vm` always uses the VM
+//!
+//! `--set decompile` prints the program as annotated pseudo-code first, for working out what it
+//! does by hand
+
+use crate::elfcode::{Instruction, Op, Program, Vm};
+use crate::input::{read_input, InputError};
+use crate::options;
+use crate::stopwatch;
+use std::collections::{BTreeMap, HashSet};
+
+/// The program's only reference to register 0: the instruction number, and the register it
+/// compares with register 0
+fn halt_check(program: &Program) -> Option<(usize, usize)> {
+    program
+        .instructions
+        .iter()
+        .enumerate()
+        .find_map(|(ip, instruction)| match instruction.op {
+            Op::Eqrr if instruction.a == 0 => Some((ip, instruction.b)),
+            Op::Eqrr if instruction.b == 0 => Some((ip, instruction.a)),
+            _ => None,
+        })
+}
+
+/// Calls `visit` with each number register 0 is compared with, in order, until it returns false
+fn watch_halt_check(program: &Program, mut visit: impl FnMut(usize) -> bool) {
+    let (check, register) = halt_check(program).expect("The program never reads register 0");
+    let mut vm = Vm::new(program, [0; 6]);
+    while vm.run_until(|vm| vm.ip == check) {
+        let value = vm.registers[register];
+        if !visit(value) {
+            return;
+        }
+        // Make sure the check fails, so the program carries on to the next value
+        vm.registers[0] = value ^ 1;
+        vm.step();
+    }
+}
+
+/// The loop that works out each number the program compares with register 0, from the one before
+/// Decompiled, the real programs all do this, with their own seed (and sometimes multiplier):
+///
+/// ```text
+/// bytes = previous | 65536
+/// value = seed
+/// loop {
+///     value = (((value + (bytes & 255)) & 16777215) * 65899) & 16777215
+///     if bytes < 256 { compare value with r0 }
+///     bytes = bytes / 256    // by counting up until (n + 1) * 256 > bytes: the slow part
+/// }
+/// ```
+#[derive(Debug, PartialEq)]
+struct Hash {
+    /// What's or-ed into the previous number (65536 in the real programs)
+    or_bits: usize,
+    seed: usize,
+    multiplier: usize,
+    mask: usize,
+}
+
+impl Hash {
+    /// Picks the constants out of the program: the `bori` that starts each number, the `seti`
+    /// straight after it that sets the seed, and the `bani` and `muli` that then work on the
+    /// seed's register. Returns None if they aren't all there
+    fn find(program: &Program) -> Option<Hash> {
+        let code = &program.instructions;
+        let start = code.iter().position(|i| i.op == Op::Bori)?;
+        let (or, seed) = (code[start], code.get(start + 1)?);
+        let value = seed.c;
+        if seed.op != Op::Seti || or.a != value || or.c == value {
+            return None;
+        }
+        // The first instruction after the seed doing `op` to the value's register, in place
+        let constant = |op: Op| {
+            code[start + 2..]
+                .iter()
+                .find(|i: &&Instruction| i.op == op && i.a == value && i.c == value)
+                .map(|i| i.b)
+        };
+        Some(Hash {
+            or_bits: or.b,
+            seed: seed.a,
+            multiplier: constant(Op::Muli)?,
+            mask: constant(Op::Bani)?,
+        })
+    }
+
+    /// Returns the number compared with register 0 after `previous`
+    fn next(&self, previous: usize) -> usize {
+        let mut bytes = previous | self.or_bits;
+        let mut value = self.seed;
+        loop {
+            value = (((value + (bytes & 255)) & self.mask) * self.multiplier) & self.mask;
+            if bytes < 256 {
+                return value;
+            }
+            bytes /= 256;
+        }
+    }
+}
+
+/// Like `watch_halt_check`, but works the numbers out with the decompiled loop if the program
+/// has one (and `--set vm` wasn't given)
+fn watch(program: &Program, mut visit: impl FnMut(usize) -> bool) {
+    match Hash::find(program).filter(|_| !options::enabled("vm")) {
+        Some(hash) => {
+            explain!("Working out the numbers directly with {:?}", hash);
+            let mut value = 0;
+            loop {
+                value = hash.next(value);
+                if !visit(value) {
+                    return;
+                }
+            }
+        }
+        None => watch_halt_check(program, visit),
+    }
+}
+
+/// Returns a note about an instruction that works on bits, eg. "keep the low 8 bits", if it's
+/// masking or shifting by a power of two
+fn bit_note(op: Op, b: usize) -> Option<String> {
+    match op {
+        Op::Bani if b > 0 && (b + 1).is_power_of_two() => {
+            Some(format!("keep the low {} bits", (b + 1).trailing_zeros()))
+        }
+        Op::Muli if b > 1 && b.is_power_of_two() => {
+            Some(format!("shift left {} bits", b.trailing_zeros()))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the register holding a comparison's result, if instruction `ip` compares two values
+/// and the next instruction jumps forward by that result (ie. skips an instruction if it's true)
+fn skip_if(program: &Program, ip: usize) -> Option<usize> {
+    let compare = program.instructions.get(ip)?;
+    let jump = program.instructions.get(ip + 1)?;
+    let comparison = matches!(
+        compare.op,
+        Op::Gtir | Op::Gtri | Op::Gtrr | Op::Eqir | Op::Eqri | Op::Eqrr
+    );
+    let ip_register = program.ip_register;
+    let skips = jump.op == Op::Addr
+        && jump.c == ip_register
+        && [(jump.a, jump.b), (jump.b, jump.a)].contains(&(compare.c, ip_register));
+    if comparison && skips {
+        Some(compare.c)
+    } else {
+        None
+    }
+}
+
+/// Returns the program as pseudo-code, one numbered line per instruction, with notes on where
+/// jumps come from, loops, the halt check, and bit masks and shifts
+pub fn decompile(program: &Program) -> String {
+    let code = program.disassemble();
+    let len = code.len();
+    // The instructions that jump to each line
+    let mut jumps_from: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for ip in 0..len {
+        if let Some(target) = program.jump_target(ip).filter(|&target| target < len) {
+            jumps_from.entry(target).or_default().push(ip);
+        }
+    }
+    let width = code.iter().map(String::len).max().unwrap_or(0);
+    let mut listing = String::new();
+    for (ip, line) in code.iter().enumerate() {
+        let mut notes = Vec::new();
+        if let Some(sources) = jumps_from.get(&ip) {
+            let sources: Vec<String> = sources.iter().map(usize::to_string).collect();
+            notes.push(format!("from {}", sources.join(", ")));
+        }
+        if program.jump_target(ip).is_some_and(|target| target <= ip) {
+            notes.push("loop".to_string());
+        }
+        if halt_check(program).is_some_and(|(check, _)| check == ip) {
+            notes.push("the only read of r0: halts if it matches".to_string());
+        }
+        let instruction = program.instructions[ip];
+        notes.extend(bit_note(instruction.op, instruction.b));
+        if let Some(condition) = ip.checked_sub(1).and_then(|prev| skip_if(program, prev)) {
+            notes.push(format!("skips line {} if r{} is true", ip + 1, condition));
+        }
+        let line = if notes.is_empty() {
+            format!("{:>3}  {}", ip, line)
+        } else {
+            format!(
+                "{:>3}  {:<width$}  // {}",
+                ip,
+                line,
+                notes.join("; "),
+                width = width
+            )
+        };
+        listing += &line;
+        listing.push('\n');
+    }
+    listing
+}
+
+fn do_part1(program: &Program) -> usize {
+    let mut first = 0;
+    watch(program, |value| {
+        first = value;
+        false
+    });
+    first
+}
+
+pub fn part1() -> Result<(), InputError> {
+    let program: Program = read_input(21)?.parse().unwrap();
+    stopwatch::lap("parse");
+    if options::enabled("decompile") {
+        print!("{}", decompile(&program));
+    }
+    let answer = do_part1(&program);
+    stopwatch::lap("solve");
+    println!("Day 21 (Part 1): {}", answer);
+    Ok(())
+}
+
+fn do_part2(program: &Program) -> usize {
+    let mut seen = HashSet::new();
+    let mut last = 0;
+    watch(program, |value| {
+        if !seen.insert(value) {
+            return false;
+        }
+        last = value;
+        true
+    });
+    explain!("The program compares {} different values", seen.len());
+    last
+}
+
+pub fn part2() -> Result<(), InputError> {
+    let program: Program = read_input(21)?.parse().unwrap();
+    stopwatch::lap("parse");
+    let answer = do_part2(&program);
+    stopwatch::lap("solve");
+    println!("Day 21 (Part 2): {}", answer);
+    Ok(())
+}
+
+/// How many numbers `verify_invariants` checks the shortcut against the VM for
+const VERIFY_COUNT: usize = 100;
+
+/// Returns the first `count` numbers the program compares with register 0, by running it
+fn run_halt_check(program: &Program, count: usize) -> Vec<usize> {
+    let mut values = Vec::new();
+    watch_halt_check(program, |value| {
+        values.push(value);
+        values.len() < count
+    });
+    values
+}
+
+/// Returns the first `count` numbers the decompiled loop works out, if the program has one
+fn run_hash(program: &Program, count: usize) -> Option<Vec<usize>> {
+    let hash = Hash::find(program)?;
+    let values = (0..count).scan(0, |value, _| {
+        *value = hash.next(*value);
+        Some(*value)
+    });
+    Some(values.collect())
+}
+
+/// Checks that the decompiled loop gives the same numbers as running the program
+pub fn verify_invariants() -> Result<(), String> {
+    let program: Program = read_input(21).map_err(|err| err.to_string())?.parse()?;
+    let hash = run_hash(&program, VERIFY_COUNT).ok_or("the program has no loop to decompile")?;
+    let ran = run_halt_check(&program, VERIFY_COUNT);
+    match (0..VERIFY_COUNT).find(|&i| hash[i] != ran[i]) {
+        None => Ok(()),
+        Some(i) => Err(format!(
+            "number {} is {} running the program, but {} from the decompiled loop",
+            i + 1,
+            ran[i],
+            hash[i]
+        )),
+    }
+}
+
+/// The puzzle has no example, so here's a small program shaped like the real ones: it compares
+/// register 0 with 3, 6, 1, 4, 7, 2, 5, 0, then starts again
+#[cfg(test)]
+const EXAMPLE: &str = "#ip 4
+seti 0 0 1
+addi 1 3 1
+bani 1 7 1
+eqrr 1 0 2
+addr 2 4 4
+seti 0 0 4";
+
+#[test]
+fn test_halt_check() {
+    let program: Program = EXAMPLE.parse().unwrap();
+    assert_eq!(halt_check(&program), Some((3, 1)));
+    assert_eq!(
+        run_halt_check(&program, 10),
+        vec![3, 6, 1, 4, 7, 2, 5, 0, 3, 6]
+    );
+    // It doesn't work its numbers out like the real programs, so part 2 runs it
+    assert_eq!(Hash::find(&program), None);
+}
+
+#[test]
+fn test_example() {
+    let program: Program = EXAMPLE.parse().unwrap();
+    assert_eq!(do_part1(&program), 3);
+    assert_eq!(do_part2(&program), 0);
+    // Check the answers really do halt the program
+    for register0 in [3, 0].iter() {
+        let mut vm = Vm::new(&program, [*register0, 0, 0, 0, 0, 0]);
+        vm.run();
+    }
+}
+
+#[test]
+fn test_decompile() {
+    let program: Program = EXAMPLE.parse().unwrap();
+    let listing = decompile(&program);
+    let lines: Vec<&str> = listing.lines().map(str::trim_end).collect();
+    assert_eq!(
+        lines,
+        vec![
+            "  0  r1 = 0",
+            "  1  r1 = r1 + 3      // from 5",
+            "  2  r1 = r1 & 7      // keep the low 3 bits",
+            "  3  r2 = (r1 == r0)  // the only read of r0: halts if it matches",
+            "  4  goto 5 + r2      // skips line 5 if r2 is true",
+            "  5  goto 1           // loop",
+        ]
+    );
+}
+
+/// A program laid out like the real ones, with a made up seed
+#[cfg(test)]
+const HASH_PROGRAM: &str = "#ip 4
+seti 123 0 3
+bani 3 456 3
+eqri 3 72 3
+addr 3 4 4
+seti 0 0 4
+seti 0 6 3
+bori 3 65536 2
+seti 1234567 8 3
+bani 2 255 5
+addr 3 5 3
+bani 3 16777215 3
+muli 3 65899 3
+bani 3 16777215 3
+gtir 256 2 5
+addr 5 4 4
+addi 4 1 4
+seti 27 5 4
+seti 0 2 5
+addi 5 1 1
+muli 1 256 1
+gtrr 1 2 1
+addr 1 4 4
+addi 4 1 4
+seti 25 1 4
+addi 5 1 5
+seti 17 8 4
+setr 5 2 2
+seti 7 9 4
+eqrr 3 0 5
+addr 5 4 4
+seti 5 4 4";
+
+#[test]
+fn test_hash() {
+    let program: Program = HASH_PROGRAM.parse().unwrap();
+    assert_eq!(
+        Hash::find(&program),
+        Some(Hash {
+            or_bits: 65536,
+            seed: 1234567,
+            multiplier: 65899,
+            mask: 16777215
+        })
+    );
+    // The decompiled loop agrees with the program
+    let ran = run_halt_check(&program, 5);
+    assert_eq!(run_hash(&program, 5), Some(ran.clone()));
+    assert_eq!(do_part1(&program), ran[0]);
+    // The numbers repeat eventually, and part 2's answer is the last new one: the number after it
+    // has been compared before
+    let last = do_part2(&program);
+    let hash = Hash::find(&program).unwrap();
+    let mut value = 0;
+    let mut seen = HashSet::new();
+    while value != last {
+        value = hash.next(value);
+        assert!(seen.insert(value));
+    }
+    assert!(seen.contains(&hash.next(last)));
+}
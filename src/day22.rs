@@ -0,0 +1,325 @@
+//! Problem: https://adventofcode.com/2018/day/22
+//! A cave's regions are rocky, wet or narrow, depending on their erosion level, which depends on
+//! the cave's depth and the regions above and to the left
+//! eg. depth: 510
+//! target: 10,10
+//! Part 1: the total risk level (0 for rocky, 1 for wet, 2 for narrow) of the rectangle from the
+//! mouth of the cave to the target
+//! Part 2: the fewest minutes to reach the target, when each region type only allows some tools
+//!
+//! `Cave::rescue` returns the whole route, not just its time, and `--set route` draws it on the
+//! map of the cave
+
+use crate::input::{read_input, InputError};
+use crate::options;
+use crate::stopwatch;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::str::FromStr;
+
+/// The example from the puzzle text
+pub const EXAMPLES: &[&str] = &[include_str!("../data/examples/day22-1.txt")];
+
+/// Minutes it takes to move to the next region
+const MOVE_TIME: usize = 1;
+
+/// Minutes it takes to change tools
+const SWITCH_TIME: usize = 7;
+
+/// The tools. A region's type (0 rocky, 1 wet, 2 narrow) is also the tool that can't be used in
+/// it: rocky regions need a tool, wet ones can't have the torch, narrow ones can't have the gear
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Tool {
+    Neither = 0,
+    Torch = 1,
+    Gear = 2,
+}
+
+impl Tool {
+    fn allowed_in(self, region_type: usize) -> bool {
+        self as usize != region_type
+    }
+}
+
+/// One step of the rescue: moving to a region, or switching tools where we are
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Step {
+    pub x: usize,
+    pub y: usize,
+    /// The tool held after the step
+    pub tool: Tool,
+    /// Minutes from the start of the rescue to the end of this step
+    pub minutes: usize,
+}
+
+/// The cave, whose erosion levels are worked out as far as they're needed
+pub struct Cave {
+    depth: usize,
+    target: (usize, usize),
+    /// Erosion levels, by row then column
+    /// Every row is the same length, and they're all worked out, so the grid only ever grows
+    erosion: Vec<Vec<usize>>,
+}
+
+impl FromStr for Cave {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Cave, String> {
+        let numbers: Vec<usize> = s
+            .split(|ch: char| !ch.is_ascii_digit())
+            .filter_map(|n| n.parse().ok())
+            .collect();
+        match numbers[..] {
+            [depth, x, y] => Ok(Cave::new(depth, (x, y))),
+            _ => Err(format!("Expected a depth and target: {}", s)),
+        }
+    }
+}
+
+impl Cave {
+    fn new(depth: usize, target: (usize, usize)) -> Cave {
+        Cave {
+            depth,
+            target,
+            erosion: Vec::new(),
+        }
+    }
+
+    /// Works out the erosion levels of every region up to x,y (and as many more as we already
+    /// had), growing the grid by at least half again each time so it doesn't grow too often
+    fn extend_to(&mut self, x: usize, y: usize) {
+        let width = self.erosion.first().map_or(0, |row| row.len());
+        let height = self.erosion.len();
+        if x < width && y < height {
+            return;
+        }
+        let width = (x + 1).max(width + width / 2);
+        let height = (y + 1).max(height + height / 2);
+        self.erosion.resize(height, Vec::new());
+        for row in 0..height {
+            for column in self.erosion[row].len()..width {
+                let geologic_index = match (column, row) {
+                    (0, 0) => 0,
+                    position if position == self.target => 0,
+                    (x, 0) => x * 16807,
+                    (0, y) => y * 48271,
+                    (x, y) => self.erosion[y][x - 1] * self.erosion[y - 1][x],
+                };
+                let erosion = (geologic_index + self.depth) % 20183;
+                self.erosion[row].push(erosion);
+            }
+        }
+    }
+
+    /// Returns the type of the region at x,y: 0 rocky, 1 wet, 2 narrow
+    fn region_type(&mut self, x: usize, y: usize) -> usize {
+        self.extend_to(x, y);
+        self.erosion[y][x] % 3
+    }
+
+    /// The sum of the region types from the mouth of the cave to the target
+    fn risk_level(&mut self) -> usize {
+        let (tx, ty) = self.target;
+        (0..=ty)
+            .flat_map(|y| (0..=tx).map(move |x| (x, y)))
+            .map(|(x, y)| self.region_type(x, y))
+            .sum()
+    }
+
+    /// Returns the quickest route to the target, holding the torch, from the mouth of the cave
+    /// The first step is the start (at 0 minutes), and every step after that either moves one
+    /// region or switches tools
+    /// Dijkstra's algorithm, where each state is a region and the tool being held
+    pub fn rescue(&mut self) -> Vec<Step> {
+        let start = (0, 0, Tool::Torch);
+        let goal = (self.target.0, self.target.1, Tool::Torch);
+        let mut best: HashMap<(usize, usize, Tool), usize> = HashMap::new();
+        // The state each state was best reached from
+        let mut came_from: HashMap<(usize, usize, Tool), (usize, usize, Tool)> = HashMap::new();
+        let mut queue = BinaryHeap::new();
+        best.insert(start, 0);
+        queue.push(Reverse((0, start)));
+        while let Some(Reverse((time, state))) = queue.pop() {
+            if state == goal {
+                let mut route = Vec::new();
+                let mut here = Some(state);
+                while let Some((x, y, tool)) = here {
+                    let minutes = best[&(x, y, tool)];
+                    route.push(Step {
+                        x,
+                        y,
+                        tool,
+                        minutes,
+                    });
+                    here = came_from.get(&(x, y, tool)).cloned();
+                }
+                route.reverse();
+                return route;
+            }
+            if best.get(&state).is_some_and(|&t| t < time) {
+                continue;
+            }
+            let (x, y, tool) = state;
+            let here = self.region_type(x, y);
+            let mut next_states = Vec::new();
+            for other in [Tool::Neither, Tool::Torch, Tool::Gear].iter() {
+                if *other != tool && other.allowed_in(here) {
+                    next_states.push(((x, y, *other), time + SWITCH_TIME));
+                }
+            }
+            let neighbours = [
+                (x.checked_sub(1), Some(y)),
+                (Some(x + 1), Some(y)),
+                (Some(x), y.checked_sub(1)),
+                (Some(x), Some(y + 1)),
+            ];
+            for (nx, ny) in neighbours.iter() {
+                if let (Some(nx), Some(ny)) = (*nx, *ny) {
+                    if tool.allowed_in(self.region_type(nx, ny)) {
+                        next_states.push(((nx, ny, tool), time + MOVE_TIME));
+                    }
+                }
+            }
+            for (next, next_time) in next_states {
+                if best.get(&next).is_none_or(|&t| next_time < t) {
+                    best.insert(next, next_time);
+                    came_from.insert(next, state);
+                    queue.push(Reverse((next_time, next)));
+                }
+            }
+        }
+        unreachable!("The target can always be reached")
+    }
+
+    /// Draws the cave as far out as `route` goes, like the puzzle does (. rocky, = wet, |
+    /// narrow, M the mouth and T the target), with the regions the route passes through as X
+    pub fn render(&mut self, route: &[Step]) -> String {
+        let width = route
+            .iter()
+            .map(|step| step.x)
+            .max()
+            .unwrap_or(0)
+            .max(self.target.0)
+            + 1;
+        let height = route
+            .iter()
+            .map(|step| step.y)
+            .max()
+            .unwrap_or(0)
+            .max(self.target.1)
+            + 1;
+        let on_route: HashSet<(usize, usize)> = route.iter().map(|step| (step.x, step.y)).collect();
+        let mut map = String::new();
+        for y in 0..height {
+            for x in 0..width {
+                map.push(match (x, y) {
+                    (0, 0) => 'M',
+                    position if position == self.target => 'T',
+                    position if on_route.contains(&position) => 'X',
+                    _ => ['.', '=', '|'][self.region_type(x, y)],
+                });
+            }
+            map.push('\n');
+        }
+        map
+    }
+}
+
+pub fn part1() -> Result<(), InputError> {
+    let mut cave: Cave = read_input(22)?.parse().unwrap();
+    stopwatch::lap("parse");
+    let answer = cave.risk_level();
+    stopwatch::lap("solve");
+    println!("Day 22 (Part 1): {}", answer);
+    Ok(())
+}
+
+pub fn part2() -> Result<(), InputError> {
+    let mut cave: Cave = read_input(22)?.parse().unwrap();
+    stopwatch::lap("parse");
+    let route = cave.rescue();
+    let answer = route.last().unwrap().minutes;
+    stopwatch::lap("solve");
+    let switches = route
+        .windows(2)
+        .filter(|pair| pair[0].tool != pair[1].tool)
+        .count();
+    explain!(
+        "The route takes {} moves and {} tool switches",
+        route.len() - 1 - switches,
+        switches
+    );
+    if options::enabled("route") {
+        print!("{}", cave.render(&route));
+    }
+    let height = cave.erosion.len();
+    let width = cave.erosion[0].len();
+    explain!("Searched a {}x{} area of the cave", width, height);
+    println!("Day 22 (Part 2): {}", answer);
+    Ok(())
+}
+
+#[test]
+fn test_erosion() {
+    let mut cave: Cave = EXAMPLES[0].parse().unwrap();
+    assert_eq!(cave.region_type(0, 0), 0);
+    assert_eq!(cave.erosion[0][0], 510);
+    assert_eq!(cave.region_type(1, 1), 2);
+    assert_eq!(cave.erosion[1][1], 1805);
+    assert_eq!(cave.region_type(10, 10), 0);
+    // Looking further out grows the grid, without changing what we already had
+    assert_eq!(cave.region_type(30, 2), cave.erosion[2][30] % 3);
+    assert_eq!(cave.erosion[1][1], 1805);
+    assert_eq!(cave.erosion[0][1], 17317);
+}
+
+#[test]
+fn test_example() {
+    let mut cave: Cave = EXAMPLES[0].parse().unwrap();
+    assert_eq!(cave.risk_level(), 114);
+    assert_eq!(cave.rescue().last().unwrap().minutes, 45);
+}
+
+#[test]
+fn test_rescue_route() {
+    let mut cave: Cave = EXAMPLES[0].parse().unwrap();
+    let route = cave.rescue();
+    let first = route[0];
+    assert_eq!(
+        (first.x, first.y, first.tool, first.minutes),
+        (0, 0, Tool::Torch, 0)
+    );
+    let last = route.last().unwrap();
+    assert_eq!(
+        (last.x, last.y, last.tool, last.minutes),
+        (10, 10, Tool::Torch, 45)
+    );
+    // Every step follows the cost model: a move to a neighbouring region with the same tool, or
+    // a tool switch where we are, using a tool the region allows
+    let mut switches = 0;
+    for pair in route.windows(2) {
+        let (from, to) = (pair[0], pair[1]);
+        assert!(to.tool.allowed_in(cave.region_type(to.x, to.y)));
+        if from.tool == to.tool {
+            let distance =
+                (from.x as isize - to.x as isize).abs() + (from.y as isize - to.y as isize).abs();
+            assert_eq!(distance, 1);
+            assert_eq!(to.minutes, from.minutes + MOVE_TIME);
+        } else {
+            assert_eq!((from.x, from.y), (to.x, to.y));
+            assert!(to.tool.allowed_in(cave.region_type(from.x, from.y)));
+            assert_eq!(to.minutes, from.minutes + SWITCH_TIME);
+            switches += 1;
+        }
+    }
+    // 45 minutes is 24 moves and 3 switches
+    assert_eq!(switches, 3);
+    assert_eq!(route.len() - 1 - switches, 24);
+    let map = cave.render(&route);
+    // The route heads down from the mouth, as in the puzzle
+    assert!(map.starts_with("M=.|=.|.|=.\nX"));
+    assert_eq!(
+        map.lines().count(),
+        route.iter().map(|step| step.y).max().unwrap() + 1
+    );
+}
@@ -0,0 +1,226 @@
+//! Problem: https://adventofcode.com/2018/day/23
+//! Nanobots each have a position and a signal radius (by Manhattan distance)
+//! eg. pos=<0,0,0>, r=4
+//! Part 1: how many nanobots are in range of the one with the largest radius
+//! Part 2: the distance from 0,0,0 to the closest point in range of the most nanobots
+//!
+//! `best_point` also gives the point itself and which nanobots reach it, so a close call between
+//! several points can be checked rather than trusted
+
+use crate::input::{read_input, InputError};
+use crate::stopwatch;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::str::FromStr;
+
+/// The examples from the puzzle text (one for each part)
+pub const EXAMPLES: &[&str] = &[
+    include_str!("../data/examples/day23-1.txt"),
+    include_str!("../data/examples/day23-2.txt"),
+];
+
+pub type Point = [i64; 3];
+
+fn distance(a: &Point, b: &Point) -> i64 {
+    a.iter().zip(b).map(|(a, b)| (a - b).abs()).sum()
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Nanobot {
+    pos: Point,
+    radius: i64,
+}
+
+impl FromStr for Nanobot {
+    type Err = String;
+
+    /// eg. pos=<0,0,0>, r=4
+    fn from_str(s: &str) -> Result<Nanobot, String> {
+        let numbers = s
+            .split(|ch: char| ch != '-' && !ch.is_ascii_digit())
+            .filter(|n| !n.is_empty())
+            .map(|n| n.parse::<i64>())
+            .collect::<Result<Vec<i64>, _>>()
+            .map_err(|err| format!("Unable to parse {}: {}", s, err))?;
+        match numbers[..] {
+            [x, y, z, radius] => Ok(Nanobot {
+                pos: [x, y, z],
+                radius,
+            }),
+            _ => Err(format!("Expected a position and radius: {}", s)),
+        }
+    }
+}
+
+/// A cube of points, `size` wide (a power of two), with its lowest corner at `min`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Cube {
+    min: Point,
+    size: i64,
+}
+
+impl Cube {
+    /// Returns the distance from `point` to the nearest point in the cube
+    fn distance_to(&self, point: &Point) -> i64 {
+        (0..3)
+            .map(|axis| {
+                let (low, high) = (self.min[axis], self.min[axis] + self.size - 1);
+                (low - point[axis]).max(0) + (point[axis] - high).max(0)
+            })
+            .sum()
+    }
+
+    /// Returns how many nanobots can reach some point in the cube
+    fn bots_in_range(&self, bots: &[Nanobot]) -> usize {
+        bots.iter()
+            .filter(|bot| self.distance_to(&bot.pos) <= bot.radius)
+            .count()
+    }
+
+    /// Splits the cube into eight cubes half as wide
+    fn split(&self) -> Vec<Cube> {
+        let half = self.size / 2;
+        (0..8)
+            .map(|corner| Cube {
+                min: [
+                    self.min[0] + half * (corner & 1),
+                    self.min[1] + half * (corner >> 1 & 1),
+                    self.min[2] + half * (corner >> 2 & 1),
+                ],
+                size: half,
+            })
+            .collect()
+    }
+}
+
+/// The answer to part 2, with what it was worked out from
+#[derive(Debug, PartialEq)]
+pub struct BestPoint {
+    pub pos: Point,
+    /// The distance from the origin
+    pub distance: i64,
+    /// The indexes of the nanobots in range of the point
+    pub in_range: Vec<usize>,
+}
+
+fn parse(data: &str) -> Vec<Nanobot> {
+    data.lines()
+        .map(|line| line.parse::<Nanobot>().unwrap())
+        .collect()
+}
+
+fn do_part1(bots: &[Nanobot]) -> usize {
+    let strongest = bots.iter().max_by_key(|bot| bot.radius).unwrap();
+    bots.iter()
+        .filter(|bot| distance(&bot.pos, &strongest.pos) <= strongest.radius)
+        .count()
+}
+
+pub fn part1() -> Result<(), InputError> {
+    let bots = parse(&read_input(23)?);
+    stopwatch::lap("parse");
+    let answer = do_part1(&bots);
+    stopwatch::lap("solve");
+    println!("Day 23 (Part 1): {}", answer);
+    Ok(())
+}
+
+/// Searches a cube holding every nanobot, always splitting the cube that the most bots can reach
+/// next (breaking ties by the closest to the origin, then the smallest)
+/// The count for a cube is never less than the count for any point in it, so the first single
+/// point we take off the queue is in range of the most bots, and is the closest such point
+pub fn best_point(bots: &[Nanobot]) -> BestPoint {
+    let origin = [0, 0, 0];
+    let furthest = bots
+        .iter()
+        .flat_map(|bot| bot.pos.iter().map(|n| n.abs()))
+        .max()
+        .unwrap_or(0);
+    let mut size = 1;
+    while size < furthest * 2 + 1 {
+        size *= 2;
+    }
+    let start = Cube {
+        min: [-size / 2; 3],
+        size,
+    };
+    let key = |cube: Cube| {
+        (
+            cube.bots_in_range(bots),
+            Reverse(cube.distance_to(&origin)),
+            Reverse(cube.size),
+            cube,
+        )
+    };
+    let mut queue = BinaryHeap::new();
+    queue.push(key(start));
+    let mut cubes_searched = 0;
+    while let Some((count, Reverse(distance), _, cube)) = queue.pop() {
+        cubes_searched += 1;
+        if cube.size == 1 {
+            explain!(
+                "{:?} is in range of {} nanobots ({} cubes searched)",
+                cube.min,
+                count,
+                cubes_searched
+            );
+            let in_range = bots
+                .iter()
+                .enumerate()
+                .filter(|(_, bot)| self::distance(&bot.pos, &cube.min) <= bot.radius)
+                .map(|(i, _)| i)
+                .collect();
+            return BestPoint {
+                pos: cube.min,
+                distance,
+                in_range,
+            };
+        }
+        for child in cube.split() {
+            queue.push(key(child));
+        }
+    }
+    unreachable!("The search always narrows down to a single point")
+}
+
+pub fn part2() -> Result<(), InputError> {
+    let bots = parse(&read_input(23)?);
+    stopwatch::lap("parse");
+    let best = best_point(&bots);
+    stopwatch::lap("solve");
+    explain!("The nanobots in range are {:?}", best.in_range);
+    let answer = best.distance;
+    println!("Day 23 (Part 2): {}", answer);
+    Ok(())
+}
+
+#[test]
+fn test_cube() {
+    let cube = Cube {
+        min: [0, 0, 0],
+        size: 4,
+    };
+    assert_eq!(cube.distance_to(&[1, 2, 3]), 0);
+    assert_eq!(cube.distance_to(&[5, -1, 2]), 3);
+    let children = cube.split();
+    assert_eq!(children.len(), 8);
+    assert!(children.contains(&Cube {
+        min: [2, 0, 2],
+        size: 2
+    }));
+}
+
+#[test]
+fn test_examples() {
+    assert_eq!(do_part1(&parse(EXAMPLES[0])), 7);
+    assert_eq!(best_point(&parse(EXAMPLES[1])).distance, 36);
+}
+
+#[test]
+fn test_best_point() {
+    let best = best_point(&parse(EXAMPLES[1]));
+    assert_eq!(best.pos, [12, 12, 12]);
+    assert_eq!(best.distance, 36);
+    // The last nanobot, at 10,10,10, is just out of range
+    assert_eq!(best.in_range, vec![0, 1, 2, 3, 4]);
+}
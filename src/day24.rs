@@ -0,0 +1,284 @@
+//! Problem: https://adventofcode.com/2018/day/24
+//! The immune system and an infection fight as armies of groups of identical units. Each round,
+//! every group chooses a target, then they attack in initiative order
+//! eg. 17 units each with 5390 hit points (weak to radiation, bludgeoning) with an attack that does
+//! 4507 fire damage at initiative 2
+//! Part 1: how many units the winning army has left
+//! Part 2: how many immune system units are left with the smallest boost to their attack that
+//! lets them win
+
+use crate::executor;
+use crate::input::{read_input, InputError};
+use crate::stopwatch;
+use crate::trace;
+use std::cmp::Reverse;
+
+/// The example from the puzzle text
+pub const EXAMPLES: &[&str] = &[include_str!("../data/examples/day24-1.txt")];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Army {
+    ImmuneSystem,
+    Infection,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Group {
+    army: Army,
+    units: usize,
+    hit_points: usize,
+    weaknesses: Vec<String>,
+    immunities: Vec<String>,
+    damage: usize,
+    attack_type: String,
+    initiative: usize,
+}
+
+impl Group {
+    /// Reads a group's description (the army comes from the heading it's under)
+    fn parse(army: Army, s: &str) -> Result<Group, String> {
+        let error = || format!("Unable to parse group: {}", s);
+        let number = |text: &str| text.parse::<usize>().map_err(|_| error());
+        let words: Vec<&str> = s.split_whitespace().collect();
+        let word_after = |marker: &str| {
+            words
+                .iter()
+                .position(|word| *word == marker)
+                .and_then(|i| words.get(i + 1))
+                .cloned()
+                .ok_or_else(error)
+        };
+        let (mut weaknesses, mut immunities) = (Vec::new(), Vec::new());
+        if let Some((_, modifiers)) = s.split_once('(') {
+            let (modifiers, _) = modifiers.split_once(')').ok_or_else(error)?;
+            for modifier in modifiers.split("; ") {
+                let (kind, types) = modifier.split_once(" to ").ok_or_else(error)?;
+                let types = types.split(", ").map(String::from);
+                match kind {
+                    "weak" => weaknesses.extend(types),
+                    "immune" => immunities.extend(types),
+                    _ => return Err(error()),
+                }
+            }
+        }
+        Ok(Group {
+            army,
+            units: number(words.first().ok_or_else(error)?)?,
+            hit_points: number(word_after("with")?)?,
+            weaknesses,
+            immunities,
+            damage: number(word_after("does")?)?,
+            attack_type: s
+                .split(" damage at")
+                .next()
+                .and_then(|text| text.rsplit(' ').next())
+                .ok_or_else(error)?
+                .to_string(),
+            initiative: number(word_after("initiative")?)?,
+        })
+    }
+
+    fn effective_power(&self) -> usize {
+        self.units * self.damage
+    }
+
+    /// Returns how much damage this group would do to `target`
+    fn damage_to(&self, target: &Group) -> usize {
+        if target.immunities.contains(&self.attack_type) {
+            0
+        } else if target.weaknesses.contains(&self.attack_type) {
+            self.effective_power() * 2
+        } else {
+            self.effective_power()
+        }
+    }
+}
+
+/// Reads both armies
+fn parse(data: &str) -> Result<Vec<Group>, String> {
+    let mut army = None;
+    let mut groups = Vec::new();
+    for line in data.lines().filter(|line| !line.is_empty()) {
+        match line {
+            "Immune System:" => army = Some(Army::ImmuneSystem),
+            "Infection:" => army = Some(Army::Infection),
+            _ => {
+                let army = army.ok_or("Expected an army heading before the first group")?;
+                groups.push(Group::parse(army, line)?);
+            }
+        }
+    }
+    Ok(groups)
+}
+
+/// How a fight ended
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    /// The army won with this many units left
+    Won(Army, usize),
+    /// Neither army can kill any more units (eg. every group left is immune to the other side)
+    Stalemate,
+}
+
+/// Fights a single round, returning how many units were killed
+fn fight_round(groups: &mut Vec<Group>) -> usize {
+    // Target selection, in decreasing order of effective power, then initiative
+    let mut order: Vec<usize> = (0..groups.len()).collect();
+    order.sort_by_key(|&i| Reverse((groups[i].effective_power(), groups[i].initiative)));
+    let mut targets: Vec<Option<usize>> = vec![None; groups.len()];
+    for &attacker in &order {
+        let group = &groups[attacker];
+        targets[attacker] = (0..groups.len())
+            .filter(|&target| groups[target].army != group.army && !targets.contains(&Some(target)))
+            .filter(|&target| group.damage_to(&groups[target]) > 0)
+            .max_by_key(|&target| {
+                let defender = &groups[target];
+                (
+                    group.damage_to(defender),
+                    defender.effective_power(),
+                    defender.initiative,
+                )
+            });
+    }
+    // Attacks, in decreasing order of initiative
+    order.sort_by_key(|&i| Reverse(groups[i].initiative));
+    let mut killed = 0;
+    for attacker in order {
+        if let Some(target) = targets[attacker] {
+            // A group that's been wiped out this round doesn't get to attack
+            if groups[attacker].units == 0 {
+                continue;
+            }
+            let damage = groups[attacker].damage_to(&groups[target]);
+            let kills = (damage / groups[target].hit_points).min(groups[target].units);
+            groups[target].units -= kills;
+            killed += kills;
+        }
+    }
+    groups.retain(|group| group.units > 0);
+    killed
+}
+
+/// Fights until one army is wiped out, with the immune system's damage increased by `boost`
+fn fight(groups: &[Group], boost: usize) -> Outcome {
+    let _span = trace::span("fight").arg("boost", boost);
+    let mut groups = groups.to_vec();
+    groups
+        .iter_mut()
+        .filter(|group| group.army == Army::ImmuneSystem)
+        .for_each(|group| group.damage += boost);
+    loop {
+        let survivors = |army: Army| {
+            groups
+                .iter()
+                .filter(|group| group.army == army)
+                .map(|group| group.units)
+                .sum::<usize>()
+        };
+        match (survivors(Army::ImmuneSystem), survivors(Army::Infection)) {
+            (units, 0) => return Outcome::Won(Army::ImmuneSystem, units),
+            (0, units) => return Outcome::Won(Army::Infection, units),
+            _ => (),
+        }
+        if fight_round(&mut groups) == 0 {
+            return Outcome::Stalemate;
+        }
+    }
+}
+
+fn do_part1(groups: &[Group]) -> usize {
+    match fight(groups, 0) {
+        Outcome::Won(army, units) => {
+            explain!("{:?} wins", army);
+            units
+        }
+        Outcome::Stalemate => panic!("Without a boost the fight ends in a stalemate"),
+    }
+}
+
+pub fn part1() -> Result<(), InputError> {
+    let groups = parse(&read_input(24)?).unwrap();
+    stopwatch::lap("parse");
+    let answer = do_part1(&groups);
+    stopwatch::lap("solve");
+    println!("Day 24 (Part 1): {}", answer);
+    Ok(())
+}
+
+/// Tries bigger and bigger boosts until the immune system wins
+/// A bigger boost doesn't always help (it can turn a loss into a stalemate), so every boost is
+/// tried in turn rather than searching
+/// The boosts are tried a few at a time, on their own threads (see `executor`)
+fn do_part2(groups: &[Group]) -> usize {
+    let (boost, units) = executor::first(1, |boost| match fight(groups, boost) {
+        Outcome::Won(Army::ImmuneSystem, units) => Some(units),
+        _ => None,
+    });
+    explain!("A boost of {} lets the immune system win", boost);
+    units
+}
+
+pub fn part2() -> Result<(), InputError> {
+    let groups = parse(&read_input(24)?).unwrap();
+    stopwatch::lap("parse");
+    let answer = do_part2(&groups);
+    stopwatch::lap("solve");
+    println!("Day 24 (Part 2): {}", answer);
+    Ok(())
+}
+
+#[test]
+fn test_parse() {
+    let groups = parse(EXAMPLES[0]).unwrap();
+    assert_eq!(groups.len(), 4);
+    assert_eq!(
+        groups[1],
+        Group {
+            army: Army::ImmuneSystem,
+            units: 989,
+            hit_points: 1274,
+            weaknesses: vec!["bludgeoning".to_string(), "slashing".to_string()],
+            immunities: vec!["fire".to_string()],
+            damage: 25,
+            attack_type: "slashing".to_string(),
+            initiative: 3,
+        }
+    );
+    let plain = Group::parse(
+        Army::Infection,
+        "10 units each with 20 hit points with an attack that does 5 cold damage at initiative 9",
+    )
+    .unwrap();
+    assert!(plain.weaknesses.is_empty() && plain.immunities.is_empty());
+    assert_eq!(plain.attack_type, "cold");
+}
+
+#[test]
+fn test_fight_round() {
+    let mut groups = parse(EXAMPLES[0]).unwrap();
+    fight_round(&mut groups);
+    let units: Vec<usize> = groups.iter().map(|group| group.units).collect();
+    // Immune system group 1 is wiped out in the first round
+    assert_eq!(units, vec![905, 797, 4434]);
+}
+
+#[test]
+fn test_example() {
+    let groups = parse(EXAMPLES[0]).unwrap();
+    assert_eq!(fight(&groups, 0), Outcome::Won(Army::Infection, 5216));
+    assert_eq!(fight(&groups, 1570), Outcome::Won(Army::ImmuneSystem, 51));
+    assert_eq!(do_part2(&groups), 51);
+}
+
+#[test]
+fn test_stalemate() {
+    // Each side is immune to the other's attacks
+    let groups = parse(concat!(
+        "Immune System:\n",
+        "10 units each with 10 hit points (immune to fire) with an attack that does 5 cold damage at initiative 1\n",
+        "Infection:\n",
+        "10 units each with 10 hit points (immune to cold) with an attack that does 5 fire damage at initiative 2\n",
+    ))
+    .unwrap();
+    assert_eq!(fight(&groups, 0), Outcome::Stalemate);
+}
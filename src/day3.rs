@@ -1,12 +1,28 @@
-use std::collections::HashMap;
+//! Problem: https://adventofcode.com/2018/day/3
+//! You have a fabric with rectangles cut out of it
+//! Find how many square inches of fabric are cut my one or more rectangles
+//!
+//! `--set sheet=quadtree` stores the sheet as a quadtree rather than a HashMap of square inches
+//! `--set heatmap=day3.png` saves the fabric as a picture: the more claims cover a square inch,
+//! the brighter it is, and part 2's intact claim is outlined in red
+
+use crate::image::Image;
+use crate::input::{read_input, InputError};
+use crate::options;
+use crate::stopwatch;
+use std::cell::Cell;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
 use std::error::Error;
-use std::fs::read_to_string;
+use std::fmt;
 use std::iter::Iterator;
+use std::num::ParseIntError;
+use std::path::PathBuf;
+use std::rc::Rc;
 use std::str::FromStr;
 
-/// Problem: https://adventofcode.com/2018/day/3
-/// You have a fabric with rectangles cut out of it
-/// Find how many square inches of fabric are cut my one or more rectangles
+/// The example from the puzzle text
+pub const EXAMPLES: &[&str] = &[include_str!("../data/examples/day3-1.txt")];
 
 /// Represents a 1x1 point in a cloth
 #[derive(Hash, Debug, PartialEq, Eq, Clone)]
@@ -15,25 +31,240 @@ struct Point {
     y: usize,
 }
 
+/// A way of storing the sheet of cloth that the elves are cutting holes out of
+trait SheetBackend {
+    /// Cuts a hole in the sheet
+    fn cut(&mut self, rect: &Rect);
+    /// Return the number of attempted cuts for this square inch
+    fn cut_count(&self, x: usize, y: usize) -> usize;
+    /// Return how many square inches have been cut more than once
+    fn overlap_count(&self) -> usize;
+    /// Return how many square inches were cut once, twice, three times...
+    fn breakdown(&self) -> BTreeMap<usize, usize>;
+}
+
+/// Gets told how much the overlap count changed by, each time a cut or uncut changes it
+type Observer = Box<dyn FnMut(isize)>;
+
 /// The sheet of cloth that the elves are cutting holes out of
 struct Sheet {
     /// Count how many times each hole has had a cut attempt
     holes: HashMap<Point, usize>,
+    /// How many square inches have been cut more than once; kept up to date as claims come and go,
+    /// so asking "what if this claim wasn't there?" doesn't mean recounting the whole sheet
+    overlaps: usize,
+    observer: Option<Observer>,
 }
 
 impl Sheet {
-    /// Cuts a hole in the sheet
+    fn new() -> Sheet {
+        Sheet {
+            holes: HashMap::new(),
+            overlaps: 0,
+            observer: None,
+        }
+    }
+
+    /// Calls `observer` with the change in the overlap count whenever a cut or uncut changes it
+    fn watch(&mut self, observer: impl FnMut(isize) + 'static) {
+        self.observer = Some(Box::new(observer));
+    }
+
+    /// Takes back a claim that was cut earlier
+    fn uncut(&mut self, rect: &Rect) {
+        let mut delta = 0;
+        for (x, y) in rect.points() {
+            if let Entry::Occupied(mut entry) = self.holes.entry(Point { x, y }) {
+                *entry.get_mut() -= 1;
+                match *entry.get() {
+                    // Forget holes that are no longer cut at all
+                    0 => {
+                        entry.remove();
+                    }
+                    1 => delta -= 1,
+                    _ => (),
+                }
+            }
+        }
+        self.changed(delta);
+    }
+
+    /// Applies a change to the overlap count and tells the observer about it
+    fn changed(&mut self, delta: isize) {
+        if delta == 0 {
+            return;
+        }
+        self.overlaps = (self.overlaps as isize + delta) as usize;
+        if let Some(observer) = &mut self.observer {
+            observer(delta);
+        }
+    }
+}
+
+impl SheetBackend for Sheet {
     fn cut(&mut self, rect: &Rect) {
         // For each x,y point in rect, increase the number of times the point has been cut
-        (rect.x..=rect.right())
-            .into_iter()
-            .flat_map(|x| (rect.y..=rect.bottom()).into_iter().map(move |y| (x, y)))
-            .for_each(|(x, y)| *self.holes.entry(Point { x, y }).or_insert(0) += 1);
+        let mut delta = 0;
+        for (x, y) in rect.points() {
+            let count = self.holes.entry(Point { x, y }).or_insert(0);
+            *count += 1;
+            if *count == 2 {
+                delta += 1;
+            }
+        }
+        self.changed(delta);
     }
-    /// Return the number of attempted cuts for this square inch
-    #[cfg(test)]
     fn cut_count(&self, x: usize, y: usize) -> usize {
-        self.holes.get(&Point { x, y }).unwrap_or(&0).clone()
+        *self.holes.get(&Point { x, y }).unwrap_or(&0)
+    }
+    fn overlap_count(&self) -> usize {
+        self.overlaps
+    }
+    fn breakdown(&self) -> BTreeMap<usize, usize> {
+        let mut breakdown = BTreeMap::new();
+        self.holes
+            .values()
+            .for_each(|cuts| *breakdown.entry(*cuts).or_insert(0) += 1);
+        breakdown
+    }
+}
+
+/// A node in a `QuadSheet`, covering a square area of the sheet
+enum QuadNode {
+    /// Every square inch in the area has been cut this many times
+    Uniform(usize),
+    /// The area is split into four quarters: top left, top right, bottom left, bottom right
+    Split(Box<[QuadNode; 4]>),
+}
+
+impl QuadNode {
+    /// Cuts the part of `rect` that falls in this node's area, which is `size` inches square with
+    /// its top left corner at x,y
+    fn cut(&mut self, x: usize, y: usize, size: usize, rect: &Rect) {
+        let (right, bottom) = (x + size - 1, y + size - 1);
+        if rect.x > right || rect.right() < x || rect.y > bottom || rect.bottom() < y {
+            // The rect misses us completely
+            return;
+        }
+        let covered =
+            rect.x <= x && rect.right() >= right && rect.y <= y && rect.bottom() >= bottom;
+        match self {
+            QuadNode::Uniform(count) if covered => *count += 1,
+            QuadNode::Uniform(count) => {
+                // Only part of our area is cut, so we need to split up
+                let count = *count;
+                *self = QuadNode::Split(Box::new([
+                    QuadNode::Uniform(count),
+                    QuadNode::Uniform(count),
+                    QuadNode::Uniform(count),
+                    QuadNode::Uniform(count),
+                ]));
+                self.cut(x, y, size, rect);
+            }
+            QuadNode::Split(quarters) => {
+                let half = size / 2;
+                quarters[0].cut(x, y, half, rect);
+                quarters[1].cut(x + half, y, half, rect);
+                quarters[2].cut(x, y + half, half, rect);
+                quarters[3].cut(x + half, y + half, half, rect);
+                // If the quarters have all ended up the same, we can merge them back together
+                if let QuadNode::Uniform(first) = quarters[0] {
+                    let same =
+                        |q: &QuadNode| matches!(q, QuadNode::Uniform(count) if *count == first);
+                    if quarters.iter().all(same) {
+                        *self = QuadNode::Uniform(first);
+                    }
+                }
+            }
+        }
+    }
+    /// Return the number of cuts at x,y, where this node is `size` inches square with its top left
+    /// corner at node_x,node_y
+    fn cut_count(&self, node_x: usize, node_y: usize, size: usize, x: usize, y: usize) -> usize {
+        match self {
+            QuadNode::Uniform(count) => *count,
+            QuadNode::Split(quarters) => {
+                let half = size / 2;
+                let (right, bottom) = (x >= node_x + half, y >= node_y + half);
+                let quarter = usize::from(right) + 2 * usize::from(bottom);
+                quarters[quarter].cut_count(
+                    node_x + half * usize::from(right),
+                    node_y + half * usize::from(bottom),
+                    half,
+                    x,
+                    y,
+                )
+            }
+        }
+    }
+    /// Return how many square inches in this node (which is `size` inches square) were cut more
+    /// than once
+    fn overlap_count(&self, size: usize) -> usize {
+        match self {
+            QuadNode::Uniform(count) if *count > 1 => size * size,
+            QuadNode::Uniform(_) => 0,
+            QuadNode::Split(quarters) => quarters.iter().map(|q| q.overlap_count(size / 2)).sum(),
+        }
+    }
+    /// Adds the square inches in this node (which is `size` inches square) to `breakdown`, by how
+    /// many times they were cut
+    fn add_breakdown(&self, size: usize, breakdown: &mut BTreeMap<usize, usize>) {
+        match self {
+            QuadNode::Uniform(0) => (),
+            QuadNode::Uniform(count) => *breakdown.entry(*count).or_insert(0) += size * size,
+            QuadNode::Split(quarters) => quarters
+                .iter()
+                .for_each(|q| q.add_breakdown(size / 2, breakdown)),
+        }
+    }
+}
+
+/// A sheet stored as a quadtree, so areas that have all been cut the same number of times are
+/// stored as a single node, no matter how big they are
+/// This keeps huge, sparse sets of claims small, where `Sheet` stores every square inch
+struct QuadSheet {
+    /// The width and height of the area we cover (always a power of 2)
+    size: usize,
+    root: QuadNode,
+}
+
+impl QuadSheet {
+    fn new() -> QuadSheet {
+        QuadSheet {
+            size: 1024,
+            root: QuadNode::Uniform(0),
+        }
+    }
+}
+
+impl SheetBackend for QuadSheet {
+    fn cut(&mut self, rect: &Rect) {
+        // Double our size until the rect fits; the old tree becomes the top left quarter
+        while rect.right() >= self.size || rect.bottom() >= self.size {
+            let old = std::mem::replace(&mut self.root, QuadNode::Uniform(0));
+            self.root = QuadNode::Split(Box::new([
+                old,
+                QuadNode::Uniform(0),
+                QuadNode::Uniform(0),
+                QuadNode::Uniform(0),
+            ]));
+            self.size *= 2;
+        }
+        self.root.cut(0, 0, self.size, rect);
+    }
+    fn cut_count(&self, x: usize, y: usize) -> usize {
+        if x >= self.size || y >= self.size {
+            return 0;
+        }
+        self.root.cut_count(0, 0, self.size, x, y)
+    }
+    fn overlap_count(&self) -> usize {
+        self.root.overlap_count(self.size)
+    }
+    fn breakdown(&self) -> BTreeMap<usize, usize> {
+        let mut breakdown = BTreeMap::new();
+        self.root.add_breakdown(self.size, &mut breakdown);
+        breakdown
     }
 }
 
@@ -46,44 +277,60 @@ struct Rect {
     height: usize,
 }
 
+/// Why a claim couldn't be read
+#[derive(Debug, PartialEq)]
+enum RectError {
+    /// The line isn't shaped like `#ID @ LEFT,TOP: WIDTHxHEIGHT`
+    Shape(String),
+    /// One of the numbers in the line isn't a number
+    Number { line: String, error: ParseIntError },
+}
+
+impl fmt::Display for RectError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RectError::Shape(line) => {
+                write!(f, "Expected #ID @ LEFT,TOP: WIDTHxHEIGHT but got: {}", line)
+            }
+            RectError::Number { line, error } => write!(f, "Unable to parse {}: {}", line, error),
+        }
+    }
+}
+
+impl Error for RectError {}
+
 impl FromStr for Rect {
-    type Err = Box<Error>;
+    type Err = RectError;
 
     fn from_str(s: &str) -> Result<Rect, Self::Err> {
         // The format of the string is:
         // #123 @ 3,2: 5x4
         // #ID  @ LEFT,TOP: WIDTHxHEIGHT
-        let parts: Vec<&str> = s.split_whitespace().collect();
-        assert_eq!(parts.len(), 4);
-        let (id, pos, size) = (parts[0], parts[2], parts[3]);
-        // Parse the ID
-        let id = id.trim_start_matches('#').parse::<usize>().unwrap();
-        // Parse the pos
-        let pos: Result<Vec<usize>, _> = pos
-            // Get rid of the ':' on the end
-            .trim_end_matches(':')
-            .splitn(2, ',')
-            .map(|part| part.parse::<usize>())
-            .collect();
-        let (x, y) = match pos {
-            Ok(pos) => (pos[0], pos[1]),
-            Err(err) => return Err(err.into()),
+        let shape = || RectError::Shape(s.to_string());
+        let number = |n: &str| {
+            n.parse::<usize>().map_err(|error| RectError::Number {
+                line: s.to_string(),
+                error,
+            })
         };
-        // Parse the size
-        let size: Result<Vec<usize>, _> = size
-            .splitn(2, 'x')
-            .map(|part| part.parse::<usize>())
-            .collect();
-        let (width, height) = match size {
-            Ok(size) => (size[0], size[1]),
-            Err(err) => return Err(err.into()),
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        let (id, pos, size) = match parts[..] {
+            [id, "@", pos, size] => (id, pos, size),
+            _ => return Err(shape()),
         };
+        let id = id.strip_prefix('#').ok_or_else(shape)?;
+        // Get rid of the ':' on the end of the pos
+        let (x, y) = pos
+            .trim_end_matches(':')
+            .split_once(',')
+            .ok_or_else(shape)?;
+        let (width, height) = size.split_once('x').ok_or_else(shape)?;
         Ok(Rect {
-            id,
-            x,
-            y,
-            width,
-            height,
+            id: number(id)?,
+            x: number(x)?,
+            y: number(y)?,
+            width: number(width)?,
+            height: number(height)?,
         })
     }
 }
@@ -97,6 +344,11 @@ impl Rect {
     fn bottom(&self) -> usize {
         self.y + self.height - 1
     }
+    /// Returns every (x, y) square inch covered by this rect
+    fn points(&self) -> impl Iterator<Item = (usize, usize)> {
+        let (top, bottom) = (self.y, self.bottom());
+        (self.x..=self.right()).flat_map(move |x| (top..=bottom).map(move |y| (x, y)))
+    }
     /// Returns true if these two rects intersect
     fn intersects(&self, other: &Rect) -> bool {
         self.x <= other.right()
@@ -116,6 +368,25 @@ fn test_read_rect_from_str() {
     assert_eq!(rect.height, 4);
 }
 
+#[test]
+fn test_malformed_rects() {
+    let error = |line: &str| line.parse::<Rect>().unwrap_err();
+    for line in &[
+        "",
+        "#1 3,2: 5x4",
+        "#1 @ 3,2: 5",
+        "1 @ 3,2: 5x4",
+        "#1 @ 3;2: 5x4",
+    ] {
+        assert_eq!(error(line), RectError::Shape(line.to_string()));
+    }
+    match error("#1 @ 3,2: 5xfour") {
+        RectError::Number { line, .. } => assert_eq!(line, "#1 @ 3,2: 5xfour"),
+        other => panic!("Expected a number error, got {:?}", other),
+    }
+    assert!(matches!(error("#-1 @ 3,2: 5x4"), RectError::Number { .. }));
+}
+
 #[test]
 fn test_rect_right() {
     let r = Rect {
@@ -152,62 +423,264 @@ fn test_whole_example() {
     assert_eq!(rects[1].x, 3);
     assert_eq!(rects[2].y, 5);
     // Now cut all the holes
-    let mut sheet = Sheet {
-        holes: HashMap::new(),
-    };
-    rects.iter().for_each(|hole| sheet.cut(&hole));
+    let mut sheet = Sheet::new();
+    rects.iter().for_each(|hole| sheet.cut(hole));
     assert_eq!(sheet.cut_count(0, 3), 0);
     assert_eq!(sheet.cut_count(1, 3), 1);
     assert_eq!(sheet.cut_count(3, 3), 2);
-    let answer = sheet
-        .holes
-        .values()
-        .filter(|cut_count| **cut_count > 1)
-        .count();
-    assert_eq!(answer, 4);
+    assert_eq!(sheet.overlap_count(), 4);
     // Now check the intersections
     let (r1, r2, r3) = (&rects[0], &rects[1], &rects[2]);
-    assert!(r1.intersects(&r2));
-    assert!(r2.intersects(&r1));
-    assert!(!r1.intersects(&r3));
-    assert!(!r2.intersects(&r3));
+    assert!(r1.intersects(r2));
+    assert!(r2.intersects(r1));
+    assert!(!r1.intersects(r3));
+    assert!(!r2.intersects(r3));
 }
 
-pub fn part1() {
-    // Model the sheet of paper
-    let mut sheet = Sheet {
-        holes: HashMap::new(),
-    };
-    // Cut a bunch of holes in it
-    read_to_string("data/day3.txt")
-        .unwrap()
+pub fn part1() -> Result<(), InputError> {
+    let rects: Vec<Rect> = read_input(3)?
         .lines()
         .map(|line| line.parse::<Rect>().unwrap())
-        .for_each(|hole| sheet.cut(&hole));
-    let answer = sheet.holes.values().filter(|v| **v > 1).count();
+        .collect();
+    stopwatch::lap("parse");
+    // Model the sheet of paper with whichever backend was asked for
+    match options::get("sheet").as_deref() {
+        None | Some("hashmap") => solve_part1(Sheet::new(), &rects),
+        Some("quadtree") => solve_part1(QuadSheet::new(), &rects),
+        Some(other) => panic!("Unknown sheet {} (expected hashmap or quadtree)", other),
+    }
+    Ok(())
+}
+
+/// Cuts every claim out of `sheet` and prints how many square inches were cut more than once
+fn solve_part1<B: SheetBackend>(mut sheet: B, rects: &[Rect]) {
+    // Cut a bunch of holes in it
+    rects.iter().for_each(|hole| sheet.cut(hole));
+    let answer = sheet.overlap_count();
+    stopwatch::lap("solve");
     // The count of hole points, is the total area
     println!("Day3 (part 1): {}", answer);
+    // Show how many square inches were cut once, twice, three times...
+    if crate::explain::enabled() {
+        for (cuts, inches) in sheet.breakdown() {
+            explain!("{} square inches are claimed {} time(s)", inches, cuts);
+        }
+    }
 }
 
-pub fn part2() {
-    // Find out which rectangle doesn't overlap any others
-    let rects: Vec<Rect> = read_to_string("data/day3.txt")
-        .unwrap()
-        .lines()
-        .map(|line| line.parse::<Rect>().unwrap())
-        .collect();
-    let answer = rects
+/// Returns the rect that doesn't overlap any others
+fn find_intact(rects: &[Rect]) -> Option<&Rect> {
+    rects
         .iter()
         // All other rects should not overlap
-        .filter(|r1| {
+        .find(|r1| {
             rects
                 .iter()
                 .filter(|r2| r2 != r1)
                 .all(|r2| !r1.intersects(r2))
         })
-        .nth(0);
+}
+
+/// Draws the fabric in shades of grey, from black where nobody cut to white where the most claims
+/// overlap, with `intact` outlined in red
+fn heatmap(rects: &[Rect], intact: Option<&Rect>) -> Image {
+    let mut sheet = Sheet::new();
+    rects.iter().for_each(|hole| sheet.cut(hole));
+    let width = rects.iter().map(|rect| rect.right() + 1).max().unwrap_or(0);
+    let height = rects
+        .iter()
+        .map(|rect| rect.bottom() + 1)
+        .max()
+        .unwrap_or(0);
+    let most = sheet.breakdown().keys().last().cloned().unwrap_or(1);
+    let mut picture = Image::new(width, height);
+    for y in 0..height {
+        for x in 0..width {
+            let shade = (sheet.cut_count(x, y) * 255 / most) as u8;
+            picture.set(x, y, [shade; 3]);
+        }
+    }
+    if let Some(rect) = intact {
+        rect.points()
+            .filter(|&(x, y)| x == rect.x || x == rect.right() || y == rect.y || y == rect.bottom())
+            .for_each(|(x, y)| picture.set(x, y, [255, 0, 0]));
+    }
+    picture
+}
+
+pub fn part2() -> Result<(), InputError> {
+    // Find out which rectangle doesn't overlap any others
+    let rects: Vec<Rect> = read_input(3)?
+        .lines()
+        .map(|line| line.parse::<Rect>().unwrap())
+        .collect();
+    stopwatch::lap("parse");
+    let answer = find_intact(&rects);
+    stopwatch::lap("solve");
     match answer {
         Some(answer) => println!("Day3: part(2): {}", answer.id),
-        None => println!("Day3: part(2): {}", "UNKNOWN"),
+        None => println!("Day3: part(2): UNKNOWN"),
     };
+    if let Some(path) = options::get("heatmap") {
+        let path = PathBuf::from(path);
+        heatmap(&rects, answer)
+            .save(&path)
+            .map_err(|error| InputError::Unwritable { path, error })?;
+    }
+    Ok(())
+}
+
+/// Cross-checks the two parts: the intact claim found by part 2 must only cover square inches
+/// that part 1's sheet says were cut exactly once
+/// Also checks that the quadtree sheet agrees with the HashMap one on the part 1 answer
+pub fn verify_invariants() -> Result<(), String> {
+    let rects: Vec<Rect> = read_input(3)
+        .map_err(|err| err.to_string())?
+        .lines()
+        .map(|line| line.parse::<Rect>().unwrap())
+        .collect();
+    let mut sheet = Sheet::new();
+    let mut quad = QuadSheet::new();
+    rects.iter().for_each(|hole| {
+        sheet.cut(hole);
+        quad.cut(hole);
+    });
+    if sheet.overlap_count() != quad.overlap_count() {
+        return Err(format!(
+            "the HashMap sheet has {} square inches cut more than once, but the quadtree has {}",
+            sheet.overlap_count(),
+            quad.overlap_count()
+        ));
+    }
+    let recounted = sheet.holes.values().filter(|cuts| **cuts > 1).count();
+    if sheet.overlap_count() != recounted {
+        return Err(format!(
+            "the sheet's running overlap count is {}, but recounting gives {}",
+            sheet.overlap_count(),
+            recounted
+        ));
+    }
+    let intact = find_intact(&rects).ok_or("part 2 found no intact claim")?;
+    if let Some((x, y)) = intact.points().find(|&(x, y)| sheet.cut_count(x, y) != 1) {
+        return Err(format!(
+            "claim #{} is meant to be intact, but {},{} was cut {} times",
+            intact.id,
+            x,
+            y,
+            sheet.cut_count(x, y)
+        ));
+    }
+    // Taking the intact claim back out shouldn't change the overlaps at all
+    let changed = Rc::new(Cell::new(false));
+    let observed = Rc::clone(&changed);
+    sheet.watch(move |_| observed.set(true));
+    sheet.uncut(intact);
+    if changed.get() {
+        return Err(format!(
+            "removing claim #{} changed the overlap count, so it wasn't intact",
+            intact.id
+        ));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_heatmap() {
+    let rects: Vec<Rect> = EXAMPLES[0]
+        .lines()
+        .map(|line| line.parse::<Rect>().unwrap())
+        .collect();
+    let picture = heatmap(&rects, find_intact(&rects));
+    // Nobody cut the top left; claims 1 and 2 overlap at 3,3; claim 3 is intact
+    assert_eq!(picture.get(0, 0), [0, 0, 0]);
+    assert_eq!(picture.get(1, 3), [127, 127, 127]);
+    assert_eq!(picture.get(3, 3), [255, 255, 255]);
+    assert_eq!(picture.get(5, 5), [255, 0, 0]);
+    assert_eq!(picture.get(6, 6), [255, 0, 0]);
+}
+
+#[test]
+fn test_generated_claims_fit_the_sheet() {
+    let input = crate::generate::generate(3, 1000, 3).unwrap();
+    let rects: Vec<Rect> = input
+        .lines()
+        .map(|line| line.parse::<Rect>().unwrap())
+        .collect();
+    assert_eq!(rects.len(), 1000);
+    assert!(rects.iter().all(|r| r.right() < 1000 && r.bottom() < 1000));
+}
+
+#[test]
+fn test_quad_sheet_matches_sheet() {
+    // Differential test: both backends should agree on every square inch
+    let input = crate::generate::generate(3, 300, 11).unwrap();
+    let rects: Vec<Rect> = input
+        .lines()
+        .map(|line| line.parse::<Rect>().unwrap())
+        .collect();
+    let mut sheet = Sheet::new();
+    let mut quad = QuadSheet::new();
+    rects.iter().for_each(|rect| {
+        sheet.cut(rect);
+        quad.cut(rect);
+    });
+    assert_eq!(quad.overlap_count(), sheet.overlap_count());
+    assert_eq!(quad.breakdown(), sheet.breakdown());
+    for x in (0..1000).step_by(7) {
+        for y in (0..1000).step_by(3) {
+            assert_eq!(
+                quad.cut_count(x, y),
+                sheet.cut_count(x, y),
+                "at {},{}",
+                x,
+                y
+            );
+        }
+    }
+}
+
+#[test]
+fn test_quad_sheet_grows() {
+    let mut quad = QuadSheet::new();
+    let rect = Rect {
+        id: 1,
+        x: 3000,
+        y: 10,
+        width: 5,
+        height: 5,
+    };
+    quad.cut(&rect);
+    quad.cut(&rect);
+    assert_eq!(quad.cut_count(3002, 12), 2);
+    assert_eq!(quad.cut_count(3005, 12), 0);
+    assert_eq!(quad.overlap_count(), 25);
+}
+
+#[test]
+fn test_sheet_tracks_overlaps() {
+    let rects: Vec<Rect> = EXAMPLES[0]
+        .lines()
+        .map(|line| line.parse::<Rect>().unwrap())
+        .collect();
+    let deltas = Rc::new(std::cell::RefCell::new(Vec::new()));
+    let observed = Rc::clone(&deltas);
+    let mut sheet = Sheet::new();
+    sheet.watch(move |delta| observed.borrow_mut().push(delta));
+    rects.iter().for_each(|rect| sheet.cut(rect));
+    // Only the second claim overlaps anything
+    assert_eq!(*deltas.borrow(), vec![4]);
+    assert_eq!(sheet.overlap_count(), 4);
+    // What if the first claim wasn't there?
+    sheet.uncut(&rects[0]);
+    assert_eq!(sheet.overlap_count(), 0);
+    assert_eq!(sheet.cut_count(3, 3), 1);
+    assert_eq!(sheet.cut_count(1, 3), 0);
+    // Putting it back, and cutting the second claim again too
+    sheet.cut(&rects[0]);
+    sheet.cut(&rects[1]);
+    assert_eq!(*deltas.borrow(), vec![4, -4, 4, 12]);
+    assert_eq!(sheet.overlap_count(), 16);
+    // Removing the third claim changes nothing, so the observer isn't called
+    sheet.uncut(&rects[2]);
+    assert_eq!(deltas.borrow().len(), 4);
 }
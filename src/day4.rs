@@ -1,14 +1,33 @@
-use std::num::ParseIntError;
+//! Problem: https://adventofcode.com/2018/day/4
+//! Read the guard log and find the guard who's asleep the longest
+//! eg. [1518-11-01 00:00] Guard #10 begins shift
+//! [1518-11-01 00:05] falls asleep
+//! [1518-11-01 00:25] wakes up
+//! Part 1: the guard asleep the most minutes, times the minute they're most often asleep
+//! Part 2: of every guard and minute, the pair where that guard is most often asleep at that
+//! minute, multiplied together
+//!
+//! `--set at=1518-11-01T00:05` also asks the schedule who was on duty at that time, and whether
+//! they were asleep (the date and time can be split by a 'T' or, if quoted, a space)
+//! `--set ics=day4.ics` saves the shifts and naps as a calendar, for viewing in a calendar app
+//! `--set heatmap` draws how often each guard was asleep at each minute, marking the cells the
+//! two strategies pick
+
+use crate::input::{read_input, InputError};
+use crate::options;
+use crate::stopwatch;
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
 use std::str::FromStr;
 
-/// Problem: https://adventofcode.com/2018/day/4
-/// Read the guard log and find the guard who's asleep the longest
-/// eg. [1518-11-01 00:00] Guard #10 begins shift
-/// [1518-11-01 00:05] falls asleep
-/// [1518-11-01 00:25] wakes up
+/// The example from the puzzle text
+pub const EXAMPLES: &[&str] = &[include_str!("../data/examples/day4-1.txt")];
 
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
-struct Minute {
+pub struct Minute {
     y: usize,
     m: usize,
     d: usize,
@@ -16,39 +35,71 @@ struct Minute {
     n: usize,
 }
 
+impl Minute {
+    /// The minute in iCalendar's local time format, eg. 15181101T000500
+    fn ical(&self) -> String {
+        format!(
+            "{:04}{:02}{:02}T{:02}{:02}00",
+            self.y, self.m, self.d, self.h, self.n
+        )
+    }
+}
+
+/// Why the guard log couldn't be read
+#[derive(Debug, PartialEq)]
+pub enum LogError {
+    /// A timestamp that isn't shaped like [1518-11-01 00:00]
+    Time(String),
+    /// A "Guard #..." line without a guard number
+    GuardId(String),
+    /// A line that isn't a shift starting, a guard falling asleep or a guard waking up
+    Event(String),
+    /// An event that can't happen next, eg. a guard waking up who wasn't asleep
+    Inconsistent { event: EventType, at: Minute },
+    /// The log ends with a guard still asleep
+    NeverWoke(Minute),
+}
+
+impl fmt::Display for LogError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LogError::Time(time) => write!(f, "Unable to parse time: {}", time),
+            LogError::GuardId(line) => write!(f, "Unable to parse guard ID: {}", line),
+            LogError::Event(line) => write!(f, "Unrecognised log line: {}", line),
+            LogError::Inconsistent { event, at } => write!(
+                f,
+                "Unexpected {:?} at {:?} - the log is inconsistent",
+                event, at
+            ),
+            LogError::NeverWoke(since) => {
+                write!(f, "Guard fell asleep at {:?} and never woke", since)
+            }
+        }
+    }
+}
+
+impl Error for LogError {}
+
 impl FromStr for Minute {
-    type Err = ParseIntError;
+    type Err = LogError;
 
     fn from_str(s: &str) -> Result<Minute, Self::Err> {
-        // String format is [1518-11-01 00:00]
-        let parts: Vec<&str> = s
+        // String format is [1518-11-01 00:00], or 1518-11-01T00:00 on the command line
+        let numbers = s
             .trim_start_matches('[')
             .trim_end_matches(']')
-            .split_whitespace()
-            .collect();
-        assert_eq!(parts.len(), 2);
-        let (minute, time) = (parts[0], parts[1]);
-        let minute = minute
-            .split('-')
-            .map(|part| part.parse::<usize>())
-            .collect::<Result<Vec<usize>, Self::Err>>()?;
-        assert_eq!(minute.len(), 3);
-        let time = time
-            .split(':')
+            .split(|ch: char| ch == '-' || ch == ':' || ch == 'T' || ch.is_whitespace())
             .map(|part| part.parse::<usize>())
-            .collect::<Result<Vec<usize>, Self::Err>>()?;
-        assert_eq!(time.len(), 2);
-        Ok(Minute {
-            y: minute[0],
-            m: minute[1],
-            d: minute[2],
-            h: time[0],
-            n: time[1],
-        })
+            .collect::<Result<Vec<usize>, _>>();
+        match numbers.as_deref() {
+            Ok(&[y, m, d, h, n]) => Ok(Minute { y, m, d, h, n }),
+            _ => Err(LogError::Time(s.to_string())),
+        }
     }
 }
 
 #[test]
+#[allow(clippy::zero_prefixed_literal)]
 fn test_minute_from_str() {
     let input = "[1518-11-01 04:28]";
     let minute = input.parse::<Minute>().unwrap();
@@ -57,11 +108,12 @@ fn test_minute_from_str() {
     assert_eq!(minute.d, 01);
     assert_eq!(minute.h, 04);
     assert_eq!(minute.n, 28);
+    assert_eq!("1518-11-01T04:28".parse::<Minute>(), Ok(minute));
 }
 
 /// The different kinds of log entry possible
 #[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
-enum EventType {
+pub enum EventType {
     /// Guard n started his shift
     ShiftStart(usize),
     /// Current guard went to sleep
@@ -74,17 +126,16 @@ impl FromStr for EventType {
     /// eg. [1518-11-01 00:00] Guard #10 begins shift
     /// [1518-11-01 00:05] falls asleep
     /// [1518-11-01 00:25] wakes up
-    type Err = String;
+    type Err = LogError;
 
     fn from_str(s: &str) -> Result<EventType, Self::Err> {
         // We'll assume that the [date-part] is gone and we're left with everything after "] "
         use EventType::*;
         if s.starts_with("Guard #") {
-            s.splitn(2, '#')
-                .nth(1)
-                .and_then(|s| s.splitn(2, ' ').nth(0))
+            s.split_once('#')
+                .and_then(|(_, s)| s.split(' ').next())
                 .and_then(|num| num.parse::<usize>().ok())
-                .map_or(Err(format!("Unable to parse guard ID: {} ", s)), |num| {
+                .map_or(Err(LogError::GuardId(s.to_string())), |num| {
                     Ok(ShiftStart(num))
                 })
         } else if s == "falls asleep" {
@@ -92,7 +143,7 @@ impl FromStr for EventType {
         } else if s == "wakes up" {
             Ok(Wake)
         } else {
-            Err(format!("Unrecognised log line: {}", s))
+            Err(LogError::Event(s.to_string()))
         }
     }
 }
@@ -115,15 +166,13 @@ struct LogEntry {
 }
 
 impl FromStr for LogEntry {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<LogEntry, String> {
-        let parts: Vec<&str> = s.splitn(2, ']').collect();
-        assert_eq!(parts.len(), 2);
-        let (minute, event) = (parts[0], parts[1]);
-        let minute: Minute = minute.parse().map_err(|e: ParseIntError| {
-            format!("Unable to parse minute: error: {} - input: {}", e, s)
-        })?;
+    type Err = LogError;
+
+    fn from_str(s: &str) -> Result<LogEntry, LogError> {
+        let (minute, event) = s
+            .split_once(']')
+            .ok_or_else(|| LogError::Time(s.to_string()))?;
+        let minute: Minute = minute.parse()?;
         let event: EventType = event.trim().parse()?;
         Ok(LogEntry { minute, event })
     }
@@ -188,5 +237,448 @@ fn test_log_entry_sort() {
     assert_eq!(entries[0], shift10);
     assert_eq!(entries[1], sleep);
     assert_eq!(entries[2], wake);
+}
+
+#[test]
+/// A sorted generated log should read shift, sleep, wake, sleep, wake...
+fn test_generated_log_is_consistent() {
+    let input = crate::generate::generate(4, 100, 3).unwrap();
+    let mut entries: Vec<LogEntry> = input.lines().map(|line| line.parse().unwrap()).collect();
+    entries.sort();
+    let mut last = EventType::Wake;
+    for entry in entries {
+        use EventType::*;
+        match (&last, &entry.event) {
+            (Wake, ShiftStart(_)) | (ShiftStart(_), Sleep) | (Wake, Sleep) | (Sleep, Wake) => (),
+            (a, b) => panic!("{:?} followed by {:?} at {:?}", a, b, entry.minute),
+        }
+        last = entry.event;
+    }
+}
+
+/// One guard's shift: who was on duty, and when they were asleep
+#[derive(Debug)]
+struct Shift {
+    guard: usize,
+    start: Minute,
+    /// (fell asleep, woke up) pairs; the guard is awake again at the second minute
+    naps: Vec<(Minute, Minute)>,
+}
+
+/// Every shift in the log, in time order
+/// Each shift lasts until the next one starts
+#[derive(Debug)]
+pub struct Schedule {
+    shifts: Vec<Shift>,
+}
+
+impl FromStr for Schedule {
+    type Err = LogError;
+
+    /// Reads a whole guard log (in any order) and reconstructs the shifts
+    fn from_str(s: &str) -> Result<Schedule, LogError> {
+        let mut entries = s
+            .lines()
+            .map(|line| line.parse::<LogEntry>())
+            .collect::<Result<Vec<LogEntry>, LogError>>()?;
+        entries.sort();
+        let mut shifts: Vec<Shift> = Vec::new();
+        // When the current guard fell asleep, if they're asleep
+        let mut asleep_since: Option<Minute> = None;
+        for entry in entries {
+            use EventType::*;
+            match (entry.event, shifts.last_mut(), asleep_since.take()) {
+                (ShiftStart(guard), _, None) => shifts.push(Shift {
+                    guard,
+                    start: entry.minute,
+                    naps: Vec::new(),
+                }),
+                (Sleep, Some(_), None) => asleep_since = Some(entry.minute),
+                (Wake, Some(shift), Some(since)) => shift.naps.push((since, entry.minute)),
+                (event, _, _) => {
+                    return Err(LogError::Inconsistent {
+                        event,
+                        at: entry.minute,
+                    })
+                }
+            }
+        }
+        match asleep_since {
+            Some(since) => Err(LogError::NeverWoke(since)),
+            None => Ok(Schedule { shifts }),
+        }
+    }
+}
+
+impl Schedule {
+    /// Returns the guard on duty at `time` and whether they were asleep, or None if no shift had
+    /// started yet
+    pub fn at(&self, time: &Minute) -> Option<(usize, bool)> {
+        // The shift on duty is the last one to start at or before `time`
+        let started = self.shifts.partition_point(|shift| shift.start <= *time);
+        let shift = &self.shifts[started.checked_sub(1)?];
+        let asleep = shift
+            .naps
+            .iter()
+            .any(|(sleep, wake)| sleep <= time && time < wake);
+        Some((shift.guard, asleep))
+    }
+
+    /// For each guard, the fraction of their shifts in which they were asleep at `minute` past
+    /// midnight (strategy 2 looks for the guard and minute with the highest count of these)
+    pub fn asleep_probability(&self, minute: usize) -> BTreeMap<usize, f64> {
+        // guard -> (shifts worked, shifts asleep at `minute`)
+        let mut counts: BTreeMap<usize, (usize, usize)> = BTreeMap::new();
+        for shift in &self.shifts {
+            let asleep = shift
+                .naps
+                .iter()
+                .any(|(sleep, wake)| sleep.h == 0 && sleep.n <= minute && minute < wake.n);
+            let count = counts.entry(shift.guard).or_insert((0, 0));
+            count.0 += 1;
+            count.1 += usize::from(asleep);
+        }
+        counts
+            .into_iter()
+            .map(|(guard, (shifts, asleep))| (guard, asleep as f64 / shifts as f64))
+            .collect()
+    }
+
+    /// Writes every shift and nap as an event in an iCalendar (.ics) file
+    /// A shift lasts until the next one starts; the last one ends when its guard last wakes up
+    pub fn ics(&self) -> String {
+        let mut events = Vec::new();
+        for (i, shift) in self.shifts.iter().enumerate() {
+            let end = match self.shifts.get(i + 1) {
+                Some(next) => &next.start,
+                None => shift.naps.last().map_or(&shift.start, |(_, wake)| wake),
+            };
+            events.push((format!("Guard #{} on duty", shift.guard), &shift.start, end));
+            for (sleep, wake) in &shift.naps {
+                events.push((format!("Guard #{} asleep", shift.guard), sleep, wake));
+            }
+        }
+        let mut lines = vec![
+            "BEGIN:VCALENDAR".to_string(),
+            "VERSION:2.0".to_string(),
+            "PRODID:-//aoc//Day 4 guard schedule//EN".to_string(),
+        ];
+        for (i, (summary, start, end)) in events.into_iter().enumerate() {
+            lines.extend(vec![
+                "BEGIN:VEVENT".to_string(),
+                format!("UID:day4-{}@aoc", i),
+                // Stamped with its own start, so the file is the same every time it's made
+                format!("DTSTAMP:{}Z", start.ical()),
+                format!("DTSTART:{}", start.ical()),
+                format!("DTEND:{}", end.ical()),
+                format!("SUMMARY:{}", summary),
+                "END:VEVENT".to_string(),
+            ]);
+        }
+        lines.push("END:VCALENDAR".to_string());
+        // iCalendar lines end in CRLF
+        lines.into_iter().map(|line| line + "\r\n").collect()
+    }
+
+    /// For each guard, how many of their shifts they were asleep in at each minute past midnight
+    fn nights_asleep(&self) -> BTreeMap<usize, [usize; 60]> {
+        let mut nights: BTreeMap<usize, [usize; 60]> = BTreeMap::new();
+        for shift in &self.shifts {
+            let minutes = nights.entry(shift.guard).or_insert([0; 60]);
+            for (sleep, wake) in &shift.naps {
+                (sleep.n..wake.n).for_each(|minute| minutes[minute] += 1);
+            }
+        }
+        nights
+    }
+}
+
+/// Returns the minute `minutes` holds the highest count for (the earliest, on a tie)
+fn sleepiest_minute(minutes: &[usize; 60]) -> usize {
+    (0..60).rev().max_by_key(|&minute| minutes[minute]).unwrap()
+}
+
+fn parse(data: &str) -> Schedule {
+    data.parse().unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// Strategy 1: the guard asleep the most minutes in total, and the minute they're most often
+/// asleep
+/// Returns the guard and the minute
+fn strategy1(schedule: &Schedule) -> (usize, usize) {
+    let nights = schedule.nights_asleep();
+    let (guard, minutes) = nights
+        .iter()
+        .max_by_key(|(_, minutes)| minutes.iter().sum::<usize>())
+        .expect("The log has no shifts");
+    (*guard, sleepiest_minute(minutes))
+}
+
+fn do_part1(schedule: &Schedule) -> usize {
+    let (guard, minute) = strategy1(schedule);
+    guard * minute
+}
+
+pub fn part1() -> Result<(), InputError> {
+    let schedule = parse(&read_input(4)?);
+    stopwatch::lap("parse");
+    let answer = do_part1(&schedule);
+    stopwatch::lap("solve");
+    println!("Day 4 (Part 1): {}", answer);
+    if let Some(time) = options::get("at") {
+        let minute: Minute = time.parse().unwrap_or_else(|err| panic!("{}", err));
+        match schedule.at(&minute) {
+            Some((guard, asleep)) => println!(
+                "At {}, guard #{} was {}",
+                time,
+                guard,
+                if asleep { "asleep" } else { "awake" }
+            ),
+            None => println!("At {}, no guard was on duty yet", time),
+        }
+    }
+    if let Some(path) = options::get("ics") {
+        let path = PathBuf::from(path);
+        fs::write(&path, schedule.ics()).map_err(|error| InputError::Unwritable { path, error })?;
+    }
+    Ok(())
+}
+
+/// Strategy 2: the guard and minute where that guard was asleep on the most nights
+/// Returns the guard and the minute
+fn do_part2(schedule: &Schedule) -> (usize, usize) {
+    schedule
+        .nights_asleep()
+        .iter()
+        .map(|(&guard, minutes)| (guard, sleepiest_minute(minutes), minutes))
+        .max_by_key(|&(_, minute, minutes)| minutes[minute])
+        .map(|(guard, minute, _)| (guard, minute))
+        .expect("The log has no shifts")
+}
+
+/// Shades each guard's minutes by how many nights they were asleep then, darkest for the most
+/// nights anyone was asleep at any minute. Strategy 1's cell is drawn as a '1', strategy 2's as a
+/// '2' (or '*' if they're the same)
+fn heatmap(schedule: &Schedule) -> String {
+    const SHADES: [char; 5] = [' ', '░', '▒', '▓', '█'];
+    let nights = schedule.nights_asleep();
+    let most = nights.values().flatten().cloned().max().unwrap_or(0).max(1);
+    let picks = [strategy1(schedule), do_part2(schedule)];
+    let mut map = format!("{:>7} ", "minute");
+    map.extend((0..60).map(|minute| {
+        if minute % 10 == 0 {
+            char::from(b'0' + minute as u8 / 10)
+        } else {
+            ' '
+        }
+    }));
+    map = map.trim_end().to_string() + "\n";
+    for (guard, minutes) in &nights {
+        map += &format!("{:>7} ", format!("#{}", guard));
+        map.extend(minutes.iter().enumerate().map(|(minute, &count)| {
+            match (picks[0] == (*guard, minute), picks[1] == (*guard, minute)) {
+                (true, true) => '*',
+                (true, false) => '1',
+                (false, true) => '2',
+                // Any nights at all get at least the lightest shade
+                _ => SHADES[(count * 4).div_ceil(most)],
+            }
+        }));
+        map += &format!(" {}\n", minutes.iter().sum::<usize>());
+    }
+    map
+}
+
+pub fn part2() -> Result<(), InputError> {
+    let schedule = parse(&read_input(4)?);
+    stopwatch::lap("parse");
+    let (guard, minute) = do_part2(&schedule);
+    stopwatch::lap("solve");
+    // Put the count in context: how likely each guard was to be asleep at that minute
+    for (other, probability) in schedule.asleep_probability(minute) {
+        explain!(
+            "Guard #{} was asleep at 00:{:02} on {:.0}% of their shifts",
+            other,
+            minute,
+            probability * 100.0
+        );
+    }
+    if options::enabled("heatmap") {
+        print!("{}", heatmap(&schedule));
+    }
+    println!("Day 4 (Part 2): {}", guard * minute);
+    Ok(())
+}
+
+#[test]
+fn test_schedule_at() {
+    let schedule: Schedule = EXAMPLES[0].parse().unwrap();
+    assert_eq!(schedule.shifts.len(), 5);
+    let at = |time: &str| schedule.at(&time.parse().unwrap());
+    assert_eq!(at("[1518-10-31 23:59]"), None);
+    assert_eq!(at("[1518-11-01 00:04]"), Some((10, false)));
+    assert_eq!(at("[1518-11-01 00:05]"), Some((10, true)));
+    assert_eq!(at("[1518-11-01 00:25]"), Some((10, false)));
+    // Guard 99 starts before midnight
+    assert_eq!(at("[1518-11-01 23:59]"), Some((99, false)));
+    assert_eq!(at("[1518-11-02 00:45]"), Some((99, true)));
+    // Guard 10 is still on duty well after the shift ended, until the next guard arrives
+    assert_eq!(at("[1518-11-03 23:00]"), Some((10, false)));
+}
+
+#[test]
+fn test_asleep_probability() {
+    let schedule: Schedule = EXAMPLES[0].parse().unwrap();
+    // Guard 10 was asleep at minute 24 on both of their shifts
+    let at24 = schedule.asleep_probability(24);
+    assert_eq!(at24[&10], 1.0);
+    assert_eq!(at24[&99], 0.0);
+    // Guard 99 was asleep at minute 45 on all three of theirs
+    assert_eq!(schedule.asleep_probability(45)[&99], 1.0);
+    assert_eq!(schedule.asleep_probability(40)[&99], 2.0 / 3.0);
+}
+
+#[test]
+fn test_heatmap() {
+    let map = heatmap(&parse(EXAMPLES[0]));
+    let rows: Vec<&str> = map.lines().collect();
+    assert_eq!(rows.len(), 3);
+    assert!(rows[0].starts_with(" minute 0         1         2"));
+    // Guard 10 was asleep 50 minutes, peaking at minute 24 on both of their nights; guard 99 was
+    // asleep at minute 45 on all three of theirs, which strategy 2 picks
+    assert!(rows[1].starts_with("    #10      ▒▒▒▒"));
+    assert_eq!(rows[1].chars().nth(8 + 24), Some('1'));
+    assert!(rows[1].ends_with(" 50"));
+    assert_eq!(rows[2].chars().nth(8 + 45), Some('2'));
+    assert_eq!(rows[2].chars().nth(8 + 44), Some('▓'));
+}
+
+#[test]
+fn test_ics() {
+    let ics = parse(EXAMPLES[0]).ics();
+    assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+    assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    // Five shifts, with six naps between them
+    assert_eq!(ics.matches("BEGIN:VEVENT").count(), 11);
+    assert_eq!(ics.matches("asleep").count(), 6);
+    // Guard 99 starts just before midnight, and is on duty until guard 10 arrives the next night
+    assert!(ics.contains(concat!(
+        "DTSTART:15181101T235800\r\n",
+        "DTEND:15181103T000500\r\n",
+        "SUMMARY:Guard #99 on duty\r\n"
+    )));
+    assert!(ics.contains(concat!(
+        "DTSTART:15181102T004000\r\n",
+        "DTEND:15181102T005000\r\n",
+        "SUMMARY:Guard #99 asleep\r\n"
+    )));
+}
+
+#[test]
+fn test_inconsistent_log() {
+    let log = "[1518-11-01 00:00] Guard #10 begins shift\n[1518-11-01 00:25] wakes up";
+    assert_eq!(
+        log.parse::<Schedule>().unwrap_err(),
+        LogError::Inconsistent {
+            event: EventType::Wake,
+            at: "[1518-11-01 00:25]".parse().unwrap()
+        }
+    );
+    let log = "[1518-11-01 00:05] falls asleep";
+    assert!(matches!(
+        log.parse::<Schedule>(),
+        Err(LogError::Inconsistent {
+            event: EventType::Sleep,
+            ..
+        })
+    ));
+    let log = "[1518-11-01 00:00] Guard #10 begins shift\n[1518-11-01 00:05] falls asleep";
+    assert!(matches!(
+        log.parse::<Schedule>(),
+        Err(LogError::NeverWoke(Minute { n: 5, .. }))
+    ));
+}
 
+#[test]
+fn test_malformed_log_lines() {
+    let error = |line: &str| line.parse::<LogEntry>().unwrap_err();
+    assert_eq!(
+        error("1518-11-01 00:00 falls asleep"),
+        LogError::Time("1518-11-01 00:00 falls asleep".to_string())
+    );
+    assert_eq!(
+        error("[1518-11-01] falls asleep"),
+        LogError::Time("[1518-11-01".to_string())
+    );
+    assert_eq!(
+        error("[1518-11-01 00:xx] falls asleep"),
+        LogError::Time("[1518-11-01 00:xx".to_string())
+    );
+    assert_eq!(
+        error("[1518-11-01 00:00] Guard #ten begins shift"),
+        LogError::GuardId("Guard #ten begins shift".to_string())
+    );
+    assert_eq!(
+        error("[1518-11-01 00:00] sleepwalks"),
+        LogError::Event("sleepwalks".to_string())
+    );
+}
+
+#[test]
+fn test_examples() {
+    let schedule = parse(EXAMPLES[0]);
+    assert_eq!(do_part1(&schedule), 240);
+    assert_eq!(do_part2(&schedule), (99, 45));
+}
+
+/// Checks part 1's minute against a histogram built a different way: by asking the schedule
+/// whether the chosen guard was asleep at each minute past midnight, on every night they napped
+fn check_strategy1(schedule: &Schedule) -> Result<(), String> {
+    let (guard, minute) = strategy1(schedule);
+    let nights: BTreeSet<(usize, usize, usize)> = schedule
+        .shifts
+        .iter()
+        .filter(|shift| shift.guard == guard)
+        .flat_map(|shift| {
+            shift
+                .naps
+                .iter()
+                .map(|(sleep, _)| (sleep.y, sleep.m, sleep.d))
+        })
+        .collect();
+    let mut histogram = [0; 60];
+    for &(y, m, d) in &nights {
+        for (n, count) in histogram.iter_mut().enumerate() {
+            let time = Minute { y, m, d, h: 0, n };
+            if schedule.at(&time) == Some((guard, true)) {
+                *count += 1;
+            }
+        }
+    }
+    let peak = sleepiest_minute(&histogram);
+    if peak == minute {
+        Ok(())
+    } else {
+        Err(format!(
+            "part 1 chose minute {} for guard #{}, but their histogram peaks at minute {} ({} \
+             nights against {})",
+            minute, guard, peak, histogram[peak], histogram[minute]
+        ))
+    }
+}
+
+/// Cross-checks part 1: the strategy 1 guard's sleep histogram must peak at the reported minute
+pub fn verify_invariants() -> Result<(), String> {
+    let schedule: Schedule = read_input(4)
+        .map_err(|err| err.to_string())?
+        .parse()
+        .map_err(|err: LogError| err.to_string())?;
+    check_strategy1(&schedule)
+}
+
+#[test]
+fn test_check_strategy1() {
+    assert_eq!(check_strategy1(&parse(EXAMPLES[0])), Ok(()));
+    let generated = crate::generate::generate(4, 300, 5).unwrap();
+    assert_eq!(check_strategy1(&parse(&generated)), Ok(()));
 }
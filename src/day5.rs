@@ -0,0 +1,148 @@
+//! Problem: https://adventofcode.com/2018/day/5
+//! A polymer is a string of units (letters). A unit and its opposite polarity (the same letter in
+//! the other case) destroy each other when they end up next to each other
+//! eg. dabAcCaCBAcCcaDA reacts down to dabCBAcaDA
+//!
+//! `react` and `react_traced` give back the reacted polymer itself (and which units destroyed each
+//! other), for callers that want more than its length
+
+use crate::input::{read_input, InputError};
+use crate::stopwatch;
+
+/// The example from the puzzle text
+pub const EXAMPLES: &[&str] = &[include_str!("../data/examples/day5-1.txt")];
+
+/// Returns true if two units react: the same type, but opposite polarities
+fn reacts(a: u8, b: u8) -> bool {
+    a != b && a.eq_ignore_ascii_case(&b)
+}
+
+/// Two units that destroyed each other, by their positions in the polymer
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Reaction {
+    pub left: usize,
+    pub right: usize,
+}
+
+/// Fully reacts a polymer, leaving out every unit that `remove` returns true for
+/// Each unit is pushed onto a stack, unless it reacts with the unit on top, in which case they
+/// both go. Reactions can only ever expose the unit below, so one pass is enough
+/// If `trace` is given, every reaction is added to it in the order they happen
+fn reduce(
+    polymer: &[u8],
+    remove: impl Fn(u8) -> bool,
+    mut trace: Option<&mut Vec<Reaction>>,
+) -> Vec<u8> {
+    // Each unit on the stack is kept with its position, for the trace
+    let mut stack: Vec<(usize, u8)> = Vec::with_capacity(polymer.len());
+    for (right, &unit) in polymer
+        .iter()
+        .enumerate()
+        .filter(|(_, &unit)| !remove(unit))
+    {
+        match stack.last() {
+            Some(&(left, top)) if reacts(top, unit) => {
+                stack.pop();
+                if let Some(trace) = trace.as_mut() {
+                    trace.push(Reaction { left, right });
+                }
+            }
+            _ => stack.push((right, unit)),
+        }
+    }
+    stack.into_iter().map(|(_, unit)| unit).collect()
+}
+
+/// Returns what's left of `polymer` once it fully reacts
+/// Surrounding whitespace is ignored
+pub fn react(polymer: &str) -> String {
+    let reacted = reduce(polymer.trim().as_bytes(), |_| false, None);
+    String::from_utf8(reacted).unwrap()
+}
+
+/// Like `react`, but also returns every pair of units that destroyed each other, in the order
+/// they reacted. Positions are into the trimmed polymer
+pub fn react_traced(polymer: &str) -> (String, Vec<Reaction>) {
+    let mut trace = Vec::new();
+    let reacted = reduce(polymer.trim().as_bytes(), |_| false, Some(&mut trace));
+    (String::from_utf8(reacted).unwrap(), trace)
+}
+
+/// Returns how many units are left after the polymer fully reacts
+fn do_part1(polymer: &str) -> usize {
+    if crate::explain::enabled() {
+        let (reacted, trace) = react_traced(polymer);
+        explain!(
+            "{} pairs of units reacted, leaving {} units",
+            trace.len(),
+            reacted.len()
+        );
+        return reacted.len();
+    }
+    react(polymer).len()
+}
+
+pub fn part1() -> Result<(), InputError> {
+    let data = read_input(5)?;
+    let answer = do_part1(&data);
+    stopwatch::lap("solve");
+    println!("Day 5 (Part 1): {}", answer);
+    Ok(())
+}
+
+/// Returns the length of the shortest polymer we can make by removing every unit of one type and
+/// then reacting it
+fn do_part2(polymer: &str) -> usize {
+    // Removing a unit type from the reacted polymer gives the same result as removing it from the
+    // original, and the reacted polymer is much shorter
+    let reacted = reduce(polymer.trim().as_bytes(), |_| false, None);
+    let (unit_type, length) = (b'a'..=b'z')
+        .map(|unit_type| {
+            let reduced = reduce(
+                &reacted,
+                |unit| unit.to_ascii_lowercase() == unit_type,
+                None,
+            );
+            (unit_type, reduced.len())
+        })
+        .min_by_key(|(_, length)| *length)
+        .unwrap();
+    explain!(
+        "Removing {}/{} gives the shortest polymer",
+        unit_type as char,
+        unit_type.to_ascii_uppercase() as char
+    );
+    length
+}
+
+pub fn part2() -> Result<(), InputError> {
+    let data = read_input(5)?;
+    let answer = do_part2(&data);
+    stopwatch::lap("solve");
+    println!("Day 5 (Part 2): {}", answer);
+    Ok(())
+}
+
+#[test]
+fn test_reduce() {
+    assert_eq!(react("aA"), "");
+    assert_eq!(react("abBA"), "");
+    assert_eq!(react("abAB"), "abAB");
+    assert_eq!(react("aabAAB"), "aabAAB");
+    assert_eq!(react("dabAcCaCBAcCcaDA\n"), "dabCBAcaDA");
+}
+
+#[test]
+fn test_react_traced() {
+    let (reacted, trace) = react_traced("dabAcCaCBAcCcaDA");
+    assert_eq!(reacted, "dabCBAcaDA");
+    let pair = |left, right| Reaction { left, right };
+    // cC goes first, which brings A and a together
+    assert_eq!(trace, [pair(4, 5), pair(3, 6), pair(10, 11)]);
+}
+
+#[test]
+fn test_example() {
+    assert_eq!(do_part1(EXAMPLES[0]), 10);
+    assert_eq!(do_part2(EXAMPLES[0]), 4);
+}
@@ -0,0 +1,346 @@
+//! Problem: https://adventofcode.com/2018/day/6
+//! Given a list of coordinates, each square on the grid belongs to the coordinate closest to it
+//! (by Manhattan distance), unless two or more are equally close
+//! Part 1: find the size of the largest area that isn't infinite
+//! Part 2: find the size of the region whose total distance to every coordinate is < 10000
+//!
+//! Distances can also be measured with the Chebyshev or Euclidean metric (`--set metric=...`),
+//! which makes this a small discrete Voronoi toolkit: `closest` finds each square's owner under
+//! any `Metric`
+//!
+//! `--set voronoi=day6.png` saves part 1's grid as a picture: each coordinate's area in its own
+//! colour (darker if it's infinite), squares tied between coordinates in black, and the
+//! coordinates themselves in white
+
+use crate::image::{self, Image};
+use crate::input::{read_input, InputError};
+use crate::options;
+use crate::stopwatch;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// The example from the puzzle text
+pub const EXAMPLES: &[&str] = &[include_str!("../data/examples/day6-1.txt")];
+
+/// Part 2 looks for squares whose total distance to the coordinates is under this
+const TOTAL_DISTANCE_LIMIT: i64 = 10000;
+
+/// A point on the grid, eg. "1, 6"
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Coord {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl FromStr for Coord {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Coord, String> {
+        let (x, y) = s
+            .split_once(',')
+            .ok_or_else(|| format!("Expected x, y: {}", s))?;
+        let parse = |n: &str| {
+            n.trim()
+                .parse::<i64>()
+                .map_err(|err| format!("Unable to parse {}: {}", s, err))
+        };
+        Ok(Coord {
+            x: parse(x)?,
+            y: parse(y)?,
+        })
+    }
+}
+
+/// How the distance between two squares is measured
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Metric {
+    /// Steps along the grid lines: |dx| + |dy|. The puzzle's metric
+    Manhattan,
+    /// Steps when diagonal moves are allowed too: max(|dx|, |dy|)
+    Chebyshev,
+    /// Straight line distance
+    Euclidean,
+}
+
+impl FromStr for Metric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Metric, String> {
+        match s {
+            "manhattan" => Ok(Metric::Manhattan),
+            "chebyshev" => Ok(Metric::Chebyshev),
+            "euclidean" => Ok(Metric::Euclidean),
+            _ => Err(format!(
+                "Unknown metric {} (expected manhattan, chebyshev or euclidean)",
+                s
+            )),
+        }
+    }
+}
+
+impl Metric {
+    /// Returns the metric chosen with `--set metric=...`, or Manhattan
+    fn chosen() -> Metric {
+        options::get("metric").map_or(Metric::Manhattan, |metric| {
+            metric.parse().unwrap_or_else(|err| panic!("{}", err))
+        })
+    }
+
+    /// Returns the distance between `a` and `b`
+    /// Manhattan and Chebyshev distances are whole numbers, so they compare exactly
+    pub fn distance(self, a: &Coord, b: &Coord) -> f64 {
+        let (dx, dy) = ((a.x - b.x).abs(), (a.y - b.y).abs());
+        match self {
+            Metric::Manhattan => (dx + dy) as f64,
+            Metric::Chebyshev => dx.max(dy) as f64,
+            Metric::Euclidean => ((dx * dx + dy * dy) as f64).sqrt(),
+        }
+    }
+
+    /// How far past the coordinates' bounding box part 1 has to look to tell which areas are
+    /// infinite
+    /// Under Manhattan distance any area that reaches the edge of the box carries on forever:
+    /// every square further out in that direction is even closer to the same coordinate. That
+    /// isn't true of the other metrics (an area can poke out past the box and stop), so for them
+    /// we grow the box by its own size, and call an area infinite if it still reaches the edge
+    fn margin(self, top_left: &Coord, bottom_right: &Coord) -> i64 {
+        match self {
+            Metric::Manhattan => 0,
+            Metric::Chebyshev | Metric::Euclidean => {
+                (bottom_right.x - top_left.x).max(bottom_right.y - top_left.y) + 1
+            }
+        }
+    }
+}
+
+/// The smallest rectangle holding every coordinate: (top left, bottom right)
+fn bounds(coords: &[Coord]) -> (Coord, Coord) {
+    let min_x = coords.iter().map(|c| c.x).min().unwrap();
+    let max_x = coords.iter().map(|c| c.x).max().unwrap();
+    let min_y = coords.iter().map(|c| c.y).min().unwrap();
+    let max_y = coords.iter().map(|c| c.y).max().unwrap();
+    (Coord { x: min_x, y: min_y }, Coord { x: max_x, y: max_y })
+}
+
+/// Returns the index of the coordinate closest to `point`, or None if there's a tie
+pub fn closest(coords: &[Coord], point: &Coord, metric: Metric) -> Option<usize> {
+    let mut best: Option<(usize, f64)> = None;
+    let mut tied = false;
+    for (i, coord) in coords.iter().enumerate() {
+        let distance = metric.distance(coord, point);
+        match best {
+            Some((_, best_distance)) if distance > best_distance => (),
+            Some((_, best_distance)) if distance == best_distance => tied = true,
+            _ => {
+                best = Some((i, distance));
+                tied = false;
+            }
+        }
+    }
+    if tied {
+        None
+    } else {
+        best.map(|(i, _)| i)
+    }
+}
+
+fn parse(data: &str) -> Vec<Coord> {
+    data.lines()
+        .map(|line| line.parse::<Coord>().unwrap())
+        .collect()
+}
+
+/// Who owns each square of the bounding box (grown by the metric's margin)
+struct Territory {
+    top_left: Coord,
+    /// Row by row, the index of the closest coordinate to each square (None for a tie)
+    owners: Vec<Vec<Option<usize>>>,
+    /// The coordinates whose areas carry on forever
+    infinite: HashSet<usize>,
+}
+
+/// Works out which coordinate owns each square, and which areas are infinite
+fn territory(coords: &[Coord], metric: Metric) -> Territory {
+    let (top_left, bottom_right) = bounds(coords);
+    let margin = metric.margin(&top_left, &bottom_right);
+    let top_left = Coord {
+        x: top_left.x - margin,
+        y: top_left.y - margin,
+    };
+    let bottom_right = Coord {
+        x: bottom_right.x + margin,
+        y: bottom_right.y + margin,
+    };
+    // Any area that reaches the edge of the (grown) bounding box carries on forever
+    let mut infinite: HashSet<usize> = HashSet::new();
+    let owners = (top_left.y..=bottom_right.y)
+        .map(|y| {
+            (top_left.x..=bottom_right.x)
+                .map(|x| {
+                    let owner = closest(coords, &Coord { x, y }, metric);
+                    let on_edge = x == top_left.x
+                        || x == bottom_right.x
+                        || y == top_left.y
+                        || y == bottom_right.y;
+                    if let (Some(owner), true) = (owner, on_edge) {
+                        infinite.insert(owner);
+                    }
+                    owner
+                })
+                .collect()
+        })
+        .collect();
+    Territory {
+        top_left,
+        owners,
+        infinite,
+    }
+}
+
+impl Territory {
+    /// Draws every square: each area in its own colour, darker if it's infinite, with ties in
+    /// black and the coordinates in white
+    fn draw(&self, coords: &[Coord]) -> Image {
+        let mut picture = Image::new(self.owners[0].len(), self.owners.len());
+        for (y, row) in self.owners.iter().enumerate() {
+            for (x, owner) in row.iter().enumerate() {
+                if let Some(owner) = *owner {
+                    let mut colour = image::colour(owner);
+                    if self.infinite.contains(&owner) {
+                        colour = colour.map(|c| c / 2);
+                    }
+                    picture.set(x, y, colour);
+                }
+            }
+        }
+        for coord in coords {
+            let (x, y) = (coord.x - self.top_left.x, coord.y - self.top_left.y);
+            picture.set(x as usize, y as usize, [255; 3]);
+        }
+        picture
+    }
+}
+
+/// Returns the size of the largest finite area
+fn do_part1(coords: &[Coord], metric: Metric) -> usize {
+    let Territory {
+        owners, infinite, ..
+    } = territory(coords, metric);
+    let mut areas: HashMap<usize, usize> = HashMap::new();
+    for owner in owners.into_iter().flatten().flatten() {
+        *areas.entry(owner).or_insert(0) += 1;
+    }
+    explain!(
+        "{} of the {} areas are infinite",
+        infinite.len(),
+        coords.len()
+    );
+    areas
+        .into_iter()
+        .filter(|(owner, _)| !infinite.contains(owner))
+        .map(|(_, area)| area)
+        .max()
+        .unwrap_or(0)
+}
+
+pub fn part1() -> Result<(), InputError> {
+    let coords = parse(&read_input(6)?);
+    stopwatch::lap("parse");
+    let answer = do_part1(&coords, Metric::chosen());
+    stopwatch::lap("solve");
+    if let Some(path) = options::get("voronoi") {
+        let path = PathBuf::from(path);
+        let picture = territory(&coords, Metric::chosen()).draw(&coords);
+        picture
+            .save(&path)
+            .map_err(|error| InputError::Unwritable { path, error })?;
+    }
+    println!("Day 6 (Part 1): {}", answer);
+    Ok(())
+}
+
+/// Returns how many squares have a total distance to every coordinate of less than `limit`
+fn do_part2(coords: &[Coord], limit: i64, metric: Metric) -> usize {
+    let (top_left, bottom_right) = bounds(coords);
+    // Each step outside the bounding box adds at least one to the distance to every coordinate
+    // (under every metric we have), so the region can't reach further out than this
+    let margin = limit / coords.len() as i64 + 1;
+    (top_left.y - margin..=bottom_right.y + margin)
+        .flat_map(|y| (top_left.x - margin..=bottom_right.x + margin).map(move |x| Coord { x, y }))
+        .filter(|point| {
+            let total: f64 = coords.iter().map(|c| metric.distance(c, point)).sum();
+            total < limit as f64
+        })
+        .count()
+}
+
+pub fn part2() -> Result<(), InputError> {
+    let coords = parse(&read_input(6)?);
+    stopwatch::lap("parse");
+    let answer = do_part2(&coords, TOTAL_DISTANCE_LIMIT, Metric::chosen());
+    stopwatch::lap("solve");
+    println!("Day 6 (Part 2): {}", answer);
+    Ok(())
+}
+
+#[test]
+fn test_closest() {
+    let coords = parse(EXAMPLES[0]);
+    let closest = |x, y, metric| closest(&coords, &Coord { x, y }, metric);
+    assert_eq!(closest(0, 0, Metric::Manhattan), Some(0));
+    assert_eq!(closest(4, 4, Metric::Manhattan), Some(3));
+    // 0,4 is 3 away from both 1,1 and 1,6
+    assert_eq!(closest(0, 4, Metric::Manhattan), None);
+    // ...but going diagonally, 1,6 is only 2 away
+    assert_eq!(closest(0, 4, Metric::Chebyshev), Some(1));
+}
+
+#[test]
+fn test_metrics() {
+    let (a, b) = (Coord { x: 1, y: 1 }, Coord { x: 4, y: 5 });
+    assert_eq!(Metric::Manhattan.distance(&a, &b), 7.0);
+    assert_eq!(Metric::Chebyshev.distance(&a, &b), 4.0);
+    assert_eq!(Metric::Euclidean.distance(&a, &b), 5.0);
+    assert_eq!("chebyshev".parse(), Ok(Metric::Chebyshev));
+    assert!("taxicab".parse::<Metric>().is_err());
+}
+
+#[test]
+fn test_other_metrics_find_finite_areas() {
+    // A coordinate boxed in by four others owns a finite area whatever the metric, and the four
+    // corners own infinite ones
+    let coords = parse("0, 0\n0, 10\n10, 0\n10, 10\n5, 5\n");
+    for metric in [Metric::Manhattan, Metric::Chebyshev, Metric::Euclidean] {
+        let center = (0..=10)
+            .flat_map(|y| (0..=10).map(move |x| Coord { x, y }))
+            .filter(|point| closest(&coords, point, metric) == Some(4))
+            .count();
+        assert_eq!(do_part1(&coords, metric), center, "{:?}", metric);
+    }
+}
+
+#[test]
+fn test_example() {
+    let coords = parse(EXAMPLES[0]);
+    assert_eq!(do_part1(&coords, Metric::Manhattan), 17);
+    // The puzzle's example uses a limit of 32
+    assert_eq!(do_part2(&coords, 32, Metric::Manhattan), 16);
+}
+
+#[test]
+fn test_draw() {
+    let coords = parse(EXAMPLES[0]);
+    let territory = territory(&coords, Metric::Manhattan);
+    // The example's grid runs from 1,1 to 8,9; only D and E (3 and 4) have finite areas
+    assert_eq!(territory.top_left, Coord { x: 1, y: 1 });
+    assert_eq!((territory.owners[0].len(), territory.owners.len()), (8, 9));
+    let mut infinite: Vec<usize> = territory.infinite.iter().cloned().collect();
+    infinite.sort_unstable();
+    assert_eq!(infinite, [0, 1, 2, 5]);
+    // 1,4 is tied between A and D, and 4,4 is D's
+    assert_eq!(territory.owners[3][0], None);
+    assert_eq!(territory.owners[3][3], Some(3));
+    let png = territory.draw(&coords).png();
+    assert_eq!(&png[16..24], [0, 0, 0, 8, 0, 0, 0, 9]);
+}
@@ -0,0 +1,354 @@
+//! Problem: https://adventofcode.com/2018/day/7
+//! The sleigh instructions are a list of steps, some of which must be finished before others
+//! eg. Step C must be finished before step A can begin.
+//! Part 1: the order the steps are done in, when the first available step (alphabetically) is
+//! always done next
+//! Part 2: how long it takes 5 workers to do every step, when each takes 60 seconds plus its
+//! position in the alphabet
+//!
+//! `--set timeline` prints part 2's schedule as a table, like the worked example in the puzzle
+//! `--set dot` prints the steps as a Graphviz graph before part 2's answer, labelled with who did
+//! each step and when; save the `digraph` block and view it with eg. `dot -Tsvg`
+
+use crate::input::{read_input, InputError};
+use crate::options;
+use crate::stopwatch;
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+
+/// The example from the puzzle text
+pub const EXAMPLES: &[&str] = &[include_str!("../data/examples/day7-1.txt")];
+
+/// How many workers (including you) part 2 has
+const WORKERS: usize = 5;
+
+/// How long every step takes before adding its letter's position in the alphabet
+const BASE_DURATION: usize = 60;
+
+/// Which steps must be finished before each step can begin
+#[derive(Debug)]
+struct Requirements {
+    /// Every step, mapped to the steps it depends on
+    prerequisites: BTreeMap<char, BTreeSet<char>>,
+}
+
+impl FromStr for Requirements {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Requirements, String> {
+        let mut prerequisites: BTreeMap<char, BTreeSet<char>> = BTreeMap::new();
+        for line in s.lines() {
+            // The step names are the second and eighth words
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let step = |i: usize| {
+                words
+                    .get(i)
+                    .and_then(|word| word.chars().next())
+                    .ok_or_else(|| format!("Unable to parse: {}", line))
+            };
+            let (before, after) = (step(1)?, step(7)?);
+            prerequisites.entry(before).or_default();
+            prerequisites.entry(after).or_default().insert(before);
+        }
+        Ok(Requirements { prerequisites })
+    }
+}
+
+impl Requirements {
+    /// Returns the steps that aren't in `started`, but whose prerequisites are all in `done`, in
+    /// alphabetical order
+    fn available<'a>(
+        &'a self,
+        done: &'a BTreeSet<char>,
+        started: &'a BTreeSet<char>,
+    ) -> impl Iterator<Item = char> + 'a {
+        self.prerequisites
+            .iter()
+            .filter(move |(step, needs)| !started.contains(step) && needs.is_subset(done))
+            .map(|(step, _)| *step)
+    }
+
+    /// Returns the order one worker would do the steps in
+    fn order(&self) -> String {
+        let mut done = BTreeSet::new();
+        let mut order = String::new();
+        loop {
+            let step = match self.available(&done, &done).next() {
+                Some(step) => step,
+                None => return order,
+            };
+            done.insert(step);
+            order.push(step);
+        }
+    }
+
+    /// Writes the steps as a Graphviz digraph, with an edge from every step to the steps
+    /// waiting on it. Each step's label says who did it and when, if it's in `jobs`
+    fn dot(&self, jobs: &[Job]) -> String {
+        let mut dot = String::from("digraph steps {\n    rankdir=LR;\n");
+        for step in self.prerequisites.keys() {
+            match jobs.iter().find(|job| job.step == *step) {
+                Some(job) => {
+                    dot += &format!(
+                        "    {} [label=\"{}\\nworker {}\\n{}s-{}s\"];\n",
+                        step,
+                        step,
+                        job.worker + 1,
+                        job.start,
+                        job.finish
+                    )
+                }
+                None => dot += &format!("    {};\n", step),
+            }
+        }
+        for (step, needs) in &self.prerequisites {
+            for before in needs {
+                dot += &format!("    {} -> {};\n", before, step);
+            }
+        }
+        dot += "}\n";
+        dot
+    }
+}
+
+pub fn part1() -> Result<(), InputError> {
+    let requirements: Requirements = read_input(7)?.parse().unwrap();
+    stopwatch::lap("parse");
+    let answer = requirements.order();
+    stopwatch::lap("solve");
+    println!("Day 7 (Part 1): {}", answer);
+    Ok(())
+}
+
+/// One step done by one worker, from `start` up to (but not including) `finish`
+#[derive(Debug, PartialEq)]
+pub struct Job {
+    /// Which worker did it, from 0
+    pub worker: usize,
+    pub step: char,
+    pub start: usize,
+    pub finish: usize,
+}
+
+/// Simulates a team of workers doing the steps together
+/// Whenever a worker is free, they start the first available step (alphabetically)
+struct Scheduler<'a> {
+    requirements: &'a Requirements,
+    workers: usize,
+    base_duration: usize,
+    /// Seconds since the work started
+    time: usize,
+    /// (finish time, step) for each step being worked on
+    in_progress: Vec<(usize, char)>,
+    /// Steps that have been started, including the ones that are done
+    started: BTreeSet<char>,
+    done: BTreeSet<char>,
+    /// Every step started so far, and who's doing it
+    jobs: Vec<Job>,
+}
+
+impl<'a> Scheduler<'a> {
+    fn new(requirements: &'a Requirements, workers: usize, base_duration: usize) -> Scheduler<'a> {
+        Scheduler {
+            requirements,
+            workers,
+            base_duration,
+            time: 0,
+            in_progress: Vec::new(),
+            started: BTreeSet::new(),
+            done: BTreeSet::new(),
+            jobs: Vec::new(),
+        }
+    }
+
+    /// How long `step` takes: A takes one second more than the base duration, B two more...
+    fn duration(&self, step: char) -> usize {
+        self.base_duration + (step as usize - 'A' as usize + 1)
+    }
+
+    /// Gives free workers any available steps, then moves time on to when the next step(s) finish
+    /// Returns false once every step is done
+    fn tick(&mut self) -> bool {
+        let free = self.workers - self.in_progress.len();
+        let starting: Vec<char> = self
+            .requirements
+            .available(&self.done, &self.started)
+            .take(free)
+            .collect();
+        for step in starting {
+            // Free workers take the steps in order, lowest numbered worker first
+            let worker = (0..self.workers)
+                .find(|&worker| {
+                    !self
+                        .jobs
+                        .iter()
+                        .any(|job| job.worker == worker && job.finish > self.time)
+                })
+                .unwrap();
+            let finish = self.time + self.duration(step);
+            self.started.insert(step);
+            self.in_progress.push((finish, step));
+            self.jobs.push(Job {
+                worker,
+                step,
+                start: self.time,
+                finish,
+            });
+        }
+        let finish = match self.in_progress.iter().map(|(finish, _)| *finish).min() {
+            Some(finish) => finish,
+            None => return false,
+        };
+        self.time = finish;
+        let done = &mut self.done;
+        self.in_progress.retain(|&(time, step)| {
+            if time == finish {
+                done.insert(step);
+            }
+            time != finish
+        });
+        true
+    }
+
+    /// Runs until every step is done, returning how long it took
+    fn run(&mut self) -> usize {
+        while self.tick() {}
+        self.time
+    }
+
+    /// Lays the jobs out as a table with a row for every second, like the worked example: what
+    /// each worker is doing ('.' when idle), and the steps done so far
+    fn timeline(&self) -> String {
+        let mut table = format!("{:>6}", "Second");
+        for worker in 0..self.workers {
+            table += &format!("{:>10}", format!("Worker {}", worker + 1));
+        }
+        table += "   Done\n";
+        for second in 0..=self.time {
+            let mut row = format!("{:>6}", second);
+            for worker in 0..self.workers {
+                let step = self
+                    .jobs
+                    .iter()
+                    .find(|job| job.worker == worker && (job.start..job.finish).contains(&second))
+                    .map_or('.', |job| job.step);
+                row += &format!("{:>10}", step);
+            }
+            let mut done: Vec<&Job> = self
+                .jobs
+                .iter()
+                .filter(|job| job.finish <= second)
+                .collect();
+            done.sort_by_key(|job| job.finish);
+            let done: String = done.iter().map(|job| job.step).collect();
+            row += &format!("   {}", done);
+            table += row.trim_end();
+            table.push('\n');
+        }
+        table
+    }
+}
+
+pub fn part2() -> Result<(), InputError> {
+    let requirements: Requirements = read_input(7)?.parse().unwrap();
+    stopwatch::lap("parse");
+    let mut scheduler = Scheduler::new(&requirements, WORKERS, BASE_DURATION);
+    let answer = scheduler.run();
+    stopwatch::lap("solve");
+    if options::enabled("timeline") {
+        print!("{}", scheduler.timeline());
+    }
+    if options::enabled("dot") {
+        print!("{}", requirements.dot(&scheduler.jobs));
+    }
+    println!("Day 7 (Part 2): {}", answer);
+    Ok(())
+}
+
+#[test]
+fn test_order() {
+    let requirements: Requirements = EXAMPLES[0].parse().unwrap();
+    assert_eq!(requirements.order(), "CABDFE");
+}
+
+#[test]
+fn test_scheduler_tick() {
+    let requirements: Requirements = EXAMPLES[0].parse().unwrap();
+    // The example has two workers, and no base duration
+    let mut scheduler = Scheduler::new(&requirements, 2, 0);
+    // Only C is available at first, and takes 3 seconds
+    assert!(scheduler.tick());
+    assert_eq!(scheduler.time, 3);
+    assert_eq!(scheduler.done, "C".chars().collect());
+    // Then A and F start together; A finishes first
+    assert!(scheduler.tick());
+    assert_eq!(scheduler.time, 4);
+    assert_eq!(scheduler.in_progress, vec![(9, 'F')]);
+}
+
+#[test]
+fn test_scheduler_run() {
+    let requirements: Requirements = EXAMPLES[0].parse().unwrap();
+    assert_eq!(Scheduler::new(&requirements, 2, 0).run(), 15);
+}
+
+#[test]
+fn test_timeline() {
+    let requirements: Requirements = EXAMPLES[0].parse().unwrap();
+    let mut scheduler = Scheduler::new(&requirements, 2, 0);
+    scheduler.run();
+    assert_eq!(
+        scheduler.jobs[..3],
+        [
+            Job {
+                worker: 0,
+                step: 'C',
+                start: 0,
+                finish: 3
+            },
+            Job {
+                worker: 0,
+                step: 'A',
+                start: 3,
+                finish: 4
+            },
+            Job {
+                worker: 1,
+                step: 'F',
+                start: 3,
+                finish: 9
+            },
+        ]
+    );
+    // The rows of the worked example in the puzzle text
+    let timeline = scheduler.timeline();
+    let rows: Vec<Vec<&str>> = timeline
+        .lines()
+        .map(|line| line.split_whitespace().collect())
+        .collect();
+    assert_eq!(rows.len(), 17);
+    assert_eq!(rows[1], ["0", "C", "."]);
+    assert_eq!(rows[4], ["3", "A", "F", "C"]);
+    assert_eq!(rows[5], ["4", "B", "F", "CA"]);
+    assert_eq!(rows[7], ["6", "D", "F", "CAB"]);
+    assert_eq!(rows[11], ["10", "E", ".", "CABFD"]);
+    assert_eq!(rows[16], ["15", ".", ".", "CABFDE"]);
+}
+
+#[test]
+fn test_dot() {
+    let requirements: Requirements = EXAMPLES[0].parse().unwrap();
+    let dot = requirements.dot(&[]);
+    assert!(dot.starts_with("digraph steps {\n"));
+    assert!(dot.ends_with("}\n"));
+    for edge in &[
+        "C -> A", "C -> F", "A -> B", "A -> D", "B -> E", "D -> E", "F -> E",
+    ] {
+        assert!(dot.contains(&format!("    {};\n", edge)), "{}", edge);
+    }
+    assert_eq!(dot.matches("->").count(), 7);
+    let mut scheduler = Scheduler::new(&requirements, 2, 0);
+    scheduler.run();
+    let dot = requirements.dot(&scheduler.jobs);
+    assert!(dot.contains("    F [label=\"F\\nworker 2\\n3s-9s\"];\n"));
+}
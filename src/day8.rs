@@ -0,0 +1,221 @@
+//! Problem: https://adventofcode.com/2018/day/8
+//! The license file is a flattened tree of numbers. Each node is a header (the number of child
+//! nodes, and the number of metadata entries), then its children, then its metadata entries
+//! eg. 2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2
+//!
+//! The parsed tree can be walked in pre-order or post-order, so other questions about it (depths,
+//! metadata statistics) don't need their own recursion
+
+use crate::input::{read_input, InputError};
+use crate::stopwatch;
+use std::str::FromStr;
+
+/// The example from the puzzle text
+pub const EXAMPLES: &[&str] = &[include_str!("../data/examples/day8-1.txt")];
+
+/// A node in the license tree
+#[derive(Debug, PartialEq)]
+pub struct Node {
+    pub children: Vec<Node>,
+    pub metadata: Vec<usize>,
+}
+
+impl Node {
+    /// Reads a node (and all its children) from the front of `numbers`
+    fn read(numbers: &mut impl Iterator<Item = usize>) -> Result<Node, String> {
+        let mut next = || {
+            numbers
+                .next()
+                .ok_or("The license ended part way through a node")
+        };
+        let (child_count, metadata_count) = (next()?, next()?);
+        let children = (0..child_count)
+            .map(|_| Node::read(numbers))
+            .collect::<Result<Vec<Node>, String>>()?;
+        let metadata = numbers.take(metadata_count).collect::<Vec<usize>>();
+        if metadata.len() < metadata_count {
+            return Err("The license ended part way through some metadata".to_string());
+        }
+        Ok(Node { children, metadata })
+    }
+
+    /// Returns every node in the tree (with its depth, from 0 for this node), each one before its
+    /// children
+    pub fn pre_order(&self) -> PreOrder<'_> {
+        PreOrder {
+            stack: vec![(0, self)],
+        }
+    }
+
+    /// Returns every node in the tree (with its depth, from 0 for this node), each one after its
+    /// children
+    pub fn post_order(&self) -> PostOrder<'_> {
+        PostOrder {
+            stack: vec![(0, self, false)],
+        }
+    }
+
+    /// Returns the sum of the metadata of this node and all its descendants
+    pub fn metadata_sum(&self) -> usize {
+        self.pre_order()
+            .map(|(_, node)| node.metadata.iter().sum::<usize>())
+            .sum()
+    }
+
+    /// Returns the value of the node
+    /// A node without children is worth the sum of its metadata. Otherwise, each metadata entry
+    /// refers to a child (from 1), and the node is worth the sum of the children referred to
+    pub fn value(&self) -> usize {
+        // A post-order walk gives each node's children just before it, so their values are on
+        // top of the stack by the time we get to it
+        let mut values: Vec<usize> = Vec::new();
+        for (_, node) in self.post_order() {
+            let children = values.split_off(values.len() - node.children.len());
+            let value = if children.is_empty() {
+                node.metadata.iter().sum()
+            } else {
+                node.metadata
+                    .iter()
+                    .filter_map(|&entry| entry.checked_sub(1).and_then(|i| children.get(i)))
+                    .sum()
+            };
+            values.push(value);
+        }
+        values[0]
+    }
+}
+
+/// Walks a tree parents first; see `Node::pre_order`
+pub struct PreOrder<'a> {
+    /// Nodes still to visit, with their depths; the next one is on top
+    stack: Vec<(usize, &'a Node)>,
+}
+
+impl<'a> Iterator for PreOrder<'a> {
+    type Item = (usize, &'a Node);
+
+    fn next(&mut self) -> Option<(usize, &'a Node)> {
+        let (depth, node) = self.stack.pop()?;
+        // Push the children backwards, so the first one comes off next
+        self.stack
+            .extend(node.children.iter().rev().map(|child| (depth + 1, child)));
+        Some((depth, node))
+    }
+}
+
+/// Walks a tree children first; see `Node::post_order`
+pub struct PostOrder<'a> {
+    /// Nodes still to visit, with their depths, and whether their children have been queued yet
+    stack: Vec<(usize, &'a Node, bool)>,
+}
+
+impl<'a> Iterator for PostOrder<'a> {
+    type Item = (usize, &'a Node);
+
+    fn next(&mut self) -> Option<(usize, &'a Node)> {
+        loop {
+            let (depth, node, queued) = self.stack.pop()?;
+            if queued || node.children.is_empty() {
+                return Some((depth, node));
+            }
+            // Come back to this node once its children are done
+            self.stack.push((depth, node, true));
+            self.stack.extend(
+                node.children
+                    .iter()
+                    .rev()
+                    .map(|child| (depth + 1, child, false)),
+            );
+        }
+    }
+}
+
+impl FromStr for Node {
+    type Err = String;
+
+    /// Parses the whole license; it should hold exactly one (root) node
+    fn from_str(s: &str) -> Result<Node, String> {
+        let numbers = s
+            .split_whitespace()
+            .map(|n| n.parse::<usize>())
+            .collect::<Result<Vec<usize>, _>>()
+            .map_err(|err| format!("Unable to parse the license: {}", err))?;
+        let mut numbers = numbers.into_iter();
+        let root = Node::read(&mut numbers)?;
+        match numbers.len() {
+            0 => Ok(root),
+            extra => Err(format!("{} numbers left over after the root node", extra)),
+        }
+    }
+}
+
+pub fn part1() -> Result<(), InputError> {
+    let tree: Node = read_input(8)?.parse().unwrap();
+    stopwatch::lap("parse");
+    let answer = tree.metadata_sum();
+    stopwatch::lap("solve");
+    explain!(
+        "The tree has {} nodes, and is {} deep",
+        tree.pre_order().count(),
+        tree.pre_order().map(|(depth, _)| depth + 1).max().unwrap()
+    );
+    println!("Day 8 (Part 1): {}", answer);
+    Ok(())
+}
+
+pub fn part2() -> Result<(), InputError> {
+    let tree: Node = read_input(8)?.parse().unwrap();
+    stopwatch::lap("parse");
+    let answer = tree.value();
+    stopwatch::lap("solve");
+    println!("Day 8 (Part 2): {}", answer);
+    Ok(())
+}
+
+#[test]
+fn test_parse() {
+    let tree: Node = EXAMPLES[0].parse().unwrap();
+    // A has children B and C, and C has child D
+    assert_eq!(tree.children.len(), 2);
+    assert_eq!(tree.metadata, vec![1, 1, 2]);
+    let (b, c) = (&tree.children[0], &tree.children[1]);
+    assert_eq!(
+        *b,
+        Node {
+            children: vec![],
+            metadata: vec![10, 11, 12]
+        }
+    );
+    assert_eq!(c.metadata, vec![2]);
+    assert_eq!(c.children[0].metadata, vec![99]);
+    assert!("2 3 0 3 10".parse::<Node>().is_err());
+    assert!("0 1 5 7".parse::<Node>().is_err());
+}
+
+#[test]
+fn test_example() {
+    let tree: Node = EXAMPLES[0].parse().unwrap();
+    assert_eq!(tree.metadata_sum(), 138);
+    assert_eq!(tree.value(), 66);
+    // C refers to a child it doesn't have, so it's worth nothing
+    assert_eq!(tree.children[1].value(), 0);
+}
+
+#[test]
+fn test_walks() {
+    let tree: Node = EXAMPLES[0].parse().unwrap();
+    // Name each node by its first metadata entry: A is 1, B is 10, C is 2 and D is 99
+    let names = |walk: Vec<(usize, &Node)>| -> Vec<(usize, usize)> {
+        walk.into_iter()
+            .map(|(depth, node)| (depth, node.metadata[0]))
+            .collect()
+    };
+    assert_eq!(
+        names(tree.pre_order().collect()),
+        [(0, 1), (1, 10), (1, 2), (2, 99)]
+    );
+    assert_eq!(
+        names(tree.post_order().collect()),
+        [(1, 10), (2, 99), (1, 2), (0, 1)]
+    );
+}
@@ -0,0 +1,241 @@
+//! Problem: https://adventofcode.com/2018/day/9
+//! The elves take turns placing numbered marbles into a circle. Usually a marble goes between the
+//! marbles 1 and 2 places clockwise of the current one, but a multiple of 23 is kept (and scored)
+//! along with the marble 7 places counter-clockwise
+//! eg. 10 players; last marble is worth 1618 points
+//! Part 1: the winning score
+//! Part 2: the winning score if the last marble were 100 times larger
+//!
+//! The special rule can be changed to explore variants of the game: `--set special=N` (23),
+//! `--set removal-offset=N` (7) and `--set scoring=both|kept|removed`
+
+use crate::input::{read_input, InputError};
+use crate::options;
+use crate::stopwatch;
+use crate::wide::{self, Int, Overflow};
+use std::collections::VecDeque;
+use std::str::FromStr;
+
+/// The examples from the puzzle text
+pub const EXAMPLES: &[&str] = &[
+    include_str!("../data/examples/day9-1.txt"),
+    include_str!("../data/examples/day9-2.txt"),
+    include_str!("../data/examples/day9-3.txt"),
+    include_str!("../data/examples/day9-4.txt"),
+    include_str!("../data/examples/day9-5.txt"),
+    include_str!("../data/examples/day9-6.txt"),
+];
+
+/// The players and marbles in one game
+#[derive(Debug, PartialEq)]
+pub struct Game {
+    pub players: usize,
+    pub last_marble: usize,
+}
+
+/// What a player scores when they play a special marble
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scoring {
+    /// The marble they kept, plus the one they removed (the puzzle's rule)
+    Both,
+    /// Only the marble they kept
+    Kept,
+    /// Only the marble they removed
+    Removed,
+}
+
+impl FromStr for Scoring {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Scoring, String> {
+        match s {
+            "both" => Ok(Scoring::Both),
+            "kept" => Ok(Scoring::Kept),
+            "removed" => Ok(Scoring::Removed),
+            _ => Err(format!(
+                "Unknown scoring {} (expected both, kept or removed)",
+                s
+            )),
+        }
+    }
+}
+
+/// The special rule: what happens instead of placing a marble that's a multiple of `special`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rules {
+    /// Marbles that are a multiple of this are kept rather than placed. At least 2, otherwise the
+    /// circle could empty
+    pub special: usize,
+    /// How many places counter-clockwise of the current marble the removed marble is
+    pub removal_offset: usize,
+    pub scoring: Scoring,
+}
+
+/// The rules from the puzzle text
+pub const PUZZLE_RULES: Rules = Rules {
+    special: 23,
+    removal_offset: 7,
+    scoring: Scoring::Both,
+};
+
+impl Rules {
+    /// Returns the puzzle's rules, changed by any `--set` options
+    fn chosen() -> Rules {
+        fn setting<T: FromStr>(name: &str, default: T) -> T {
+            options::get(name).map_or(default, |value| {
+                value
+                    .parse()
+                    .unwrap_or_else(|_| panic!("Unable to parse {}={}", name, value))
+            })
+        }
+        let rules = Rules {
+            special: setting("special", PUZZLE_RULES.special),
+            removal_offset: setting("removal-offset", PUZZLE_RULES.removal_offset),
+            scoring: setting("scoring", PUZZLE_RULES.scoring),
+        };
+        if rules.special < 2 {
+            panic!("special must be at least 2, not {}", rules.special);
+        }
+        rules
+    }
+
+    /// Returns the points for keeping `marble` and removing `removed`
+    fn score(&self, marble: usize, removed: usize) -> usize {
+        match self.scoring {
+            Scoring::Both => marble + removed,
+            Scoring::Kept => marble,
+            Scoring::Removed => removed,
+        }
+    }
+}
+
+impl FromStr for Game {
+    type Err = String;
+
+    /// eg. 10 players; last marble is worth 1618 points
+    fn from_str(s: &str) -> Result<Game, String> {
+        let numbers: Vec<usize> = s
+            .split_whitespace()
+            .filter_map(|word| word.parse().ok())
+            .collect();
+        match numbers[..] {
+            [players, last_marble] => Ok(Game {
+                players,
+                last_marble,
+            }),
+            _ => Err(format!("Unable to parse game: {}", s)),
+        }
+    }
+}
+
+impl Game {
+    /// Plays the game by `rules`, returning the winning score
+    pub fn high_score(&self, rules: &Rules) -> Result<Int, Overflow> {
+        let mut scores: Vec<Int> = vec![0; self.players];
+        // The circle (clockwise), always rotated so the current marble is at the back. Rotating a
+        // VecDeque a few places is cheap, where inserting into the middle of a Vec isn't
+        let mut circle: VecDeque<usize> = VecDeque::with_capacity(self.last_marble + 1);
+        circle.push_back(0);
+        for marble in 1..=self.last_marble {
+            if marble.is_multiple_of(rules.special) {
+                // The offset can be bigger than the circle early on, when it wraps around
+                circle.rotate_right(rules.removal_offset % circle.len());
+                let removed = circle.pop_back().unwrap();
+                let player = &mut scores[marble % self.players];
+                *player = wide::add(9, *player, rules.score(marble, removed) as Int)?;
+                circle.rotate_left(1);
+            } else {
+                circle.rotate_left(1);
+                circle.push_back(marble);
+            }
+        }
+        Ok(scores.into_iter().max().unwrap_or(0))
+    }
+}
+
+pub fn part1() -> Result<(), InputError> {
+    let game: Game = read_input(9)?.parse().unwrap();
+    let answer = game.high_score(&Rules::chosen())?;
+    stopwatch::lap("solve");
+    println!("Day 9 (Part 1): {}", answer);
+    Ok(())
+}
+
+pub fn part2() -> Result<(), InputError> {
+    let mut game: Game = read_input(9)?.parse().unwrap();
+    game.last_marble *= 100;
+    let answer = game.high_score(&Rules::chosen())?;
+    stopwatch::lap("solve");
+    println!("Day 9 (Part 2): {}", answer);
+    Ok(())
+}
+
+#[test]
+fn test_parse() {
+    assert_eq!(
+        EXAMPLES[1].parse::<Game>(),
+        Ok(Game {
+            players: 10,
+            last_marble: 1618
+        })
+    );
+}
+
+#[test]
+fn test_examples() {
+    let scores: Vec<Int> = EXAMPLES
+        .iter()
+        .map(|example| {
+            let game: Game = example.parse().unwrap();
+            game.high_score(&PUZZLE_RULES).unwrap()
+        })
+        .collect();
+    assert_eq!(scores, vec![32, 8317, 146373, 2764, 54718, 37305]);
+}
+
+/// Plays the game the slow, obvious way: the circle is a Vec, and `current` is an index into it
+#[cfg(test)]
+fn reference_high_score(game: &Game, rules: &Rules) -> Int {
+    let mut scores: Vec<Int> = vec![0; game.players];
+    let mut circle = vec![0];
+    let mut current = 0;
+    for marble in 1..=game.last_marble {
+        if marble % rules.special == 0 {
+            let len = circle.len();
+            let at = (current + len - rules.removal_offset % len) % len;
+            let removed = circle.remove(at);
+            scores[marble % game.players] += rules.score(marble, removed) as Int;
+            current = at % circle.len();
+        } else {
+            current = (current + 1) % circle.len() + 1;
+            circle.insert(current, marble);
+        }
+    }
+    scores.into_iter().max().unwrap_or(0)
+}
+
+#[test]
+fn test_rules_match_reference() {
+    let mut rng = crate::generate::Rng::new(9);
+    for _ in 0..500 {
+        let game = Game {
+            players: rng.range(1, 12),
+            last_marble: rng.range(0, 300),
+        };
+        let rules = Rules {
+            special: rng.range(2, 30),
+            removal_offset: rng.range(0, 40),
+            scoring: [Scoring::Both, Scoring::Kept, Scoring::Removed][rng.range(0, 2)],
+        };
+        assert_eq!(
+            game.high_score(&rules).unwrap(),
+            reference_high_score(&game, &rules),
+            "{:?} {:?}",
+            game,
+            rules
+        );
+    }
+    // ...and the reference agrees with the puzzle's examples
+    let game: Game = EXAMPLES[1].parse().unwrap();
+    assert_eq!(reference_high_score(&game, &PUZZLE_RULES), 8317);
+}
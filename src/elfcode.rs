@@ -0,0 +1,480 @@
+//! The instruction set of the wrist-mounted device, and a machine to run programs on (days 16, 19
+//! and 21)
+//! Every instruction has an opcode and three numbers: A and B are inputs (either register numbers
+//! or immediate values, depending on the opcode), and C is the register the result goes in
+
+use std::str::FromStr;
+
+/// What an instruction's A or B number means
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operand {
+    /// The number of the register holding the value
+    Register,
+    /// The value itself
+    Immediate,
+    /// The number is ignored
+    Unused,
+}
+
+/// The sixteen opcodes
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Op {
+    Addr,
+    Addi,
+    Mulr,
+    Muli,
+    Banr,
+    Bani,
+    Borr,
+    Bori,
+    Setr,
+    Seti,
+    Gtir,
+    Gtri,
+    Gtrr,
+    Eqir,
+    Eqri,
+    Eqrr,
+}
+
+impl Op {
+    /// Every opcode, in the order the puzzle lists them
+    pub const ALL: [Op; 16] = [
+        Op::Addr,
+        Op::Addi,
+        Op::Mulr,
+        Op::Muli,
+        Op::Banr,
+        Op::Bani,
+        Op::Borr,
+        Op::Bori,
+        Op::Setr,
+        Op::Seti,
+        Op::Gtir,
+        Op::Gtri,
+        Op::Gtrr,
+        Op::Eqir,
+        Op::Eqri,
+        Op::Eqrr,
+    ];
+
+    /// The opcode's name in a program listing, eg. "addr"
+    pub fn name(self) -> &'static str {
+        use Op::*;
+        match self {
+            Addr => "addr",
+            Addi => "addi",
+            Mulr => "mulr",
+            Muli => "muli",
+            Banr => "banr",
+            Bani => "bani",
+            Borr => "borr",
+            Bori => "bori",
+            Setr => "setr",
+            Seti => "seti",
+            Gtir => "gtir",
+            Gtri => "gtri",
+            Gtrr => "gtrr",
+            Eqir => "eqir",
+            Eqri => "eqri",
+            Eqrr => "eqrr",
+        }
+    }
+
+    /// Returns what the opcode does with A and B
+    pub fn operands(self) -> (Operand, Operand) {
+        use Op::*;
+        use Operand::*;
+        match self {
+            Addr | Mulr | Banr | Borr | Gtrr | Eqrr => (Register, Register),
+            Addi | Muli | Bani | Bori | Gtri | Eqri => (Register, Immediate),
+            Gtir | Eqir => (Immediate, Register),
+            Setr => (Register, Unused),
+            Seti => (Immediate, Unused),
+        }
+    }
+
+    /// The operator the opcode applies to A and B in pseudo-code, or None for the set opcodes
+    fn symbol(self) -> Option<&'static str> {
+        use Op::*;
+        match self {
+            Addr | Addi => Some("+"),
+            Mulr | Muli => Some("*"),
+            Banr | Bani => Some("&"),
+            Borr | Bori => Some("|"),
+            Setr | Seti => None,
+            Gtir | Gtri | Gtrr => Some(">"),
+            Eqir | Eqri | Eqrr => Some("=="),
+        }
+    }
+
+    /// Runs the opcode on `registers`
+    /// Returns None (leaving the registers alone) if A, B or C names a register that doesn't exist
+    pub fn apply(self, a: usize, b: usize, c: usize, registers: &mut [usize]) -> Option<()> {
+        use Op::*;
+        let reg = |n: usize| registers.get(n).cloned();
+        let result = match self {
+            Addr => reg(a)? + reg(b)?,
+            Addi => reg(a)? + b,
+            Mulr => reg(a)? * reg(b)?,
+            Muli => reg(a)? * b,
+            Banr => reg(a)? & reg(b)?,
+            Bani => reg(a)? & b,
+            Borr => reg(a)? | reg(b)?,
+            Bori => reg(a)? | b,
+            Setr => reg(a)?,
+            Seti => a,
+            Gtir => usize::from(a > reg(b)?),
+            Gtri => usize::from(reg(a)? > b),
+            Gtrr => usize::from(reg(a)? > reg(b)?),
+            Eqir => usize::from(a == reg(b)?),
+            Eqri => usize::from(reg(a)? == b),
+            Eqrr => usize::from(reg(a)? == reg(b)?),
+        };
+        *registers.get_mut(c)? = result;
+        Some(())
+    }
+}
+
+impl FromStr for Op {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Op, String> {
+        Op::ALL
+            .iter()
+            .find(|op| op.name() == s)
+            .cloned()
+            .ok_or_else(|| format!("Unknown opcode: {}", s))
+    }
+}
+
+/// One line of a program, eg. "seti 5 0 1"
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Instruction {
+    pub op: Op,
+    pub a: usize,
+    pub b: usize,
+    pub c: usize,
+}
+
+impl FromStr for Instruction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Instruction, String> {
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        let number = |n: &str| {
+            n.parse::<usize>()
+                .map_err(|err| format!("Unable to parse {}: {}", s, err))
+        };
+        match parts[..] {
+            [op, a, b, c] => Ok(Instruction {
+                op: op.parse()?,
+                a: number(a)?,
+                b: number(b)?,
+                c: number(c)?,
+            }),
+            _ => Err(format!("Expected an opcode and three numbers: {}", s)),
+        }
+    }
+}
+
+/// A program for the device, whose instruction pointer is bound to a register
+/// eg. #ip 0
+/// seti 5 0 1
+#[derive(Debug)]
+pub struct Program {
+    /// The register that mirrors the instruction pointer
+    pub ip_register: usize,
+    pub instructions: Vec<Instruction>,
+}
+
+impl Program {
+    /// Returns where instruction `ip` always jumps to (which may be past the end, halting the
+    /// program), or None if it doesn't write to the instruction pointer or the target depends on
+    /// the other registers
+    pub fn jump_target(&self, ip: usize) -> Option<usize> {
+        let Instruction { op, a, b, c } = *self.instructions.get(ip)?;
+        if c != self.ip_register {
+            return None;
+        }
+        // The instruction pointer register holds `ip` when the instruction reads it
+        let constant = |n: usize, kind: Operand| kind != Operand::Register || n == self.ip_register;
+        let (a_kind, b_kind) = op.operands();
+        if !constant(a, a_kind) || !constant(b, b_kind) {
+            return None;
+        }
+        let mut registers = [0; 6];
+        registers[self.ip_register] = ip;
+        op.apply(a, b, c, &mut registers)?;
+        Some(registers[c] + 1)
+    }
+
+    /// Returns instruction `ip` as pseudo-code, eg. "r2 = r1 + 5", "goto 7" or "halt"
+    /// Registers are r0 to r5, except that reading the instruction pointer's register gives the
+    /// instruction's own number, and writing it is a jump
+    pub fn pseudo_code(&self, ip: usize) -> String {
+        let Instruction { op, a, b, c } = self.instructions[ip];
+        if c == self.ip_register {
+            if let Some(target) = self.jump_target(ip) {
+                return if target < self.instructions.len() {
+                    format!("goto {}", target)
+                } else {
+                    "halt".to_string()
+                };
+            }
+            // A relative jump, usually to skip the next instruction if a comparison was true
+            if op == Op::Addr && (a == self.ip_register) != (b == self.ip_register) {
+                let offset = if a == self.ip_register { b } else { a };
+                return format!("goto {} + r{}", ip + 1, offset);
+            }
+        }
+        let operand = |n: usize, kind: Operand| match kind {
+            Operand::Register if n == self.ip_register => ip.to_string(),
+            Operand::Register => format!("r{}", n),
+            Operand::Immediate | Operand::Unused => n.to_string(),
+        };
+        let (a_kind, b_kind) = op.operands();
+        let value = match op.symbol() {
+            Some(symbol) => format!("{} {} {}", operand(a, a_kind), symbol, operand(b, b_kind)),
+            None => operand(a, a_kind),
+        };
+        if c == self.ip_register {
+            format!("goto ({}) + 1", value)
+        } else if op.symbol() == Some(">") || op.symbol() == Some("==") {
+            format!("r{} = ({})", c, value)
+        } else {
+            format!("r{} = {}", c, value)
+        }
+    }
+
+    /// Returns every instruction as pseudo-code
+    pub fn disassemble(&self) -> Vec<String> {
+        (0..self.instructions.len())
+            .map(|ip| self.pseudo_code(ip))
+            .collect()
+    }
+}
+
+impl FromStr for Program {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Program, String> {
+        let mut lines = s.lines();
+        let ip_register = lines
+            .next()
+            .and_then(|line| line.strip_prefix("#ip "))
+            .and_then(|n| n.trim().parse().ok())
+            .ok_or("Expected the program to start with #ip <register>")?;
+        let instructions = lines
+            .filter(|line| !line.is_empty())
+            .map(|line| line.parse::<Instruction>())
+            .collect::<Result<Vec<Instruction>, String>>()?;
+        Ok(Program {
+            ip_register,
+            instructions,
+        })
+    }
+}
+
+/// Runs a program on a device with six registers
+pub struct Vm<'a> {
+    program: &'a Program,
+    pub registers: [usize; 6],
+    /// The instruction to run next
+    pub ip: usize,
+}
+
+impl<'a> Vm<'a> {
+    pub fn new(program: &'a Program, registers: [usize; 6]) -> Vm<'a> {
+        Vm {
+            program,
+            registers,
+            ip: 0,
+        }
+    }
+
+    /// Runs one instruction, returning false (and doing nothing) if the program has halted
+    /// The instruction pointer is written to its register first, and read back afterwards, so
+    /// instructions can jump by changing that register
+    pub fn step(&mut self) -> bool {
+        let instruction = match self.program.instructions.get(self.ip) {
+            Some(instruction) => *instruction,
+            None => return false,
+        };
+        self.registers[self.program.ip_register] = self.ip;
+        let Instruction { op, a, b, c } = instruction;
+        op.apply(a, b, c, &mut self.registers)
+            .unwrap_or_else(|| panic!("Bad register in instruction {}", self.ip));
+        self.ip = self.registers[self.program.ip_register] + 1;
+        true
+    }
+
+    /// Runs until the program halts
+    pub fn run(&mut self) {
+        while self.step() {}
+    }
+
+    /// Runs until `stop` returns true (it's called before each instruction, so it can watch the
+    /// program as it goes), returning false if the program halts first
+    pub fn run_until(&mut self, mut stop: impl FnMut(&Vm) -> bool) -> bool {
+        while !stop(self) {
+            if !self.step() {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[test]
+fn test_apply() {
+    let mut registers = [3, 2, 1, 1];
+    Op::Mulr.apply(2, 1, 2, &mut registers).unwrap();
+    assert_eq!(registers, [3, 2, 2, 1]);
+    Op::Gtir.apply(3, 0, 3, &mut registers).unwrap();
+    assert_eq!(registers, [3, 2, 2, 0]);
+    assert_eq!(Op::Addr.apply(7, 0, 0, &mut registers), None);
+    assert_eq!(Op::Seti.apply(7, 0, 4, &mut registers), None);
+    assert_eq!("eqrr".parse(), Ok(Op::Eqrr));
+}
+
+#[test]
+fn test_every_opcode() {
+    // r0 = 0b1100, r1 = 0b1010, and r2 and r3 are edge values
+    let start = [12, 10, 0, usize::MAX];
+    // (opcode, a, b, what ends up in register c)
+    let cases = [
+        (Op::Addr, 0, 1, 22),
+        (Op::Addi, 0, 1, 13),
+        (Op::Addi, 0, 100, 112),
+        (Op::Mulr, 0, 1, 120),
+        (Op::Mulr, 0, 2, 0),
+        (Op::Muli, 1, 3, 30),
+        (Op::Banr, 0, 1, 8),
+        (Op::Banr, 1, 3, 10),
+        (Op::Bani, 0, 1, 0),
+        (Op::Bani, 0, 7, 4),
+        (Op::Borr, 0, 1, 14),
+        (Op::Borr, 2, 2, 0),
+        (Op::Bori, 0, 1, 13),
+        (Op::Bori, 2, 0, 0),
+        (Op::Setr, 1, 99, 10),
+        (Op::Setr, 3, 0, usize::MAX),
+        (Op::Seti, 1, 99, 1),
+        (Op::Seti, 1_000_000, 0, 1_000_000),
+        // The comparisons are strict, so equal values aren't greater
+        (Op::Gtir, 13, 0, 1),
+        (Op::Gtir, 12, 0, 0),
+        (Op::Gtir, 0, 2, 0),
+        (Op::Gtri, 0, 11, 1),
+        (Op::Gtri, 0, 12, 0),
+        (Op::Gtri, 3, 1_000_000, 1),
+        (Op::Gtrr, 0, 1, 1),
+        (Op::Gtrr, 1, 0, 0),
+        (Op::Gtrr, 0, 0, 0),
+        (Op::Eqir, 12, 0, 1),
+        (Op::Eqir, 0, 0, 0),
+        (Op::Eqir, 0, 2, 1),
+        (Op::Eqri, 0, 12, 1),
+        (Op::Eqri, 0, 0, 0),
+        (Op::Eqri, 2, 0, 1),
+        (Op::Eqrr, 0, 1, 0),
+        (Op::Eqrr, 2, 2, 1),
+        (Op::Eqrr, 3, 3, 1),
+    ];
+    for &(op, a, b, expected) in cases.iter() {
+        for c in 0..4 {
+            let mut registers = start;
+            op.apply(a, b, c, &mut registers).unwrap();
+            let mut wanted = start;
+            wanted[c] = expected;
+            assert_eq!(registers, wanted, "{} {} {} {}", op.name(), a, b, c);
+        }
+    }
+    // Every opcode is covered above
+    let covered: Vec<Op> = Op::ALL
+        .iter()
+        .cloned()
+        .filter(|op| cases.iter().any(|case| case.0 == *op))
+        .collect();
+    assert_eq!(covered, Op::ALL.to_vec());
+    // Which of A and B name registers (r), are immediate values (i), or aren't used (-)
+    let operands = [
+        (Op::Addr, 'r', 'r'),
+        (Op::Addi, 'r', 'i'),
+        (Op::Mulr, 'r', 'r'),
+        (Op::Muli, 'r', 'i'),
+        (Op::Banr, 'r', 'r'),
+        (Op::Bani, 'r', 'i'),
+        (Op::Borr, 'r', 'r'),
+        (Op::Bori, 'r', 'i'),
+        (Op::Setr, 'r', '-'),
+        (Op::Seti, 'i', '-'),
+        (Op::Gtir, 'i', 'r'),
+        (Op::Gtri, 'r', 'i'),
+        (Op::Gtrr, 'r', 'r'),
+        (Op::Eqir, 'i', 'r'),
+        (Op::Eqri, 'r', 'i'),
+        (Op::Eqrr, 'r', 'r'),
+    ];
+    for &(op, a_kind, b_kind) in operands.iter() {
+        // Only a register operand can be out of range, and then nothing is written
+        let mut registers = [1, 2, 3, 4];
+        assert_eq!(op.apply(4, 0, 0, &mut registers).is_none(), a_kind == 'r');
+        assert_eq!(op.apply(0, 4, 0, &mut registers).is_none(), b_kind == 'r');
+        let mut registers = [1, 2, 3, 4];
+        assert_eq!(op.apply(0, 0, 4, &mut registers), None, "{}", op.name());
+        assert_eq!(registers, [1, 2, 3, 4]);
+    }
+}
+
+#[test]
+fn test_vm() {
+    let program: Program = "#ip 0\nseti 5 0 1\nseti 6 0 2\naddi 0 1 0\naddr 1 2 3\nsetr 1 0 0\n\
+                            seti 8 0 4\nseti 9 0 5"
+        .parse()
+        .unwrap();
+    assert_eq!(program.ip_register, 0);
+    assert_eq!(
+        program.instructions[2],
+        Instruction {
+            op: Op::Addi,
+            a: 0,
+            b: 1,
+            c: 0
+        }
+    );
+    let mut vm = Vm::new(&program, [0; 6]);
+    assert!(vm.step());
+    assert_eq!((vm.ip, vm.registers), (1, [0, 5, 0, 0, 0, 0]));
+    vm.run();
+    assert_eq!(vm.registers, [6, 5, 6, 0, 0, 9]);
+    assert!(!vm.step());
+}
+
+#[test]
+fn test_disassemble() {
+    let program: Program = "#ip 4\nseti 0 0 1\naddi 1 3 1\neqrr 1 0 2\naddr 2 4 4\n\
+                            seti 0 0 4\nmulr 4 4 4\nbani 1 255 3\ngtir 7 4 5\naddi 4 2 4"
+        .parse()
+        .unwrap();
+    assert_eq!(
+        program.disassemble(),
+        vec![
+            "r1 = 0",
+            "r1 = r1 + 3",
+            "r2 = (r1 == r0)",
+            "goto 4 + r2",
+            "goto 1",
+            "halt",
+            "r3 = r1 & 255",
+            "r5 = (7 > 7)",
+            "halt",
+        ]
+    );
+    assert_eq!(program.jump_target(3), None);
+    assert_eq!(program.jump_target(4), Some(1));
+    assert_eq!(program.jump_target(5), Some(26));
+    assert_eq!(program.jump_target(0), None);
+    assert_eq!(Op::Gtir.operands(), (Operand::Immediate, Operand::Register));
+}
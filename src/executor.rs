@@ -0,0 +1,105 @@
+//! Runs independent trials on several threads at once, while giving the same answer as trying
+//! them one at a time
+//! Days 15 and 24 try bigger and bigger attack powers (or boosts) until one works. Each trial is
+//! a whole simulation that doesn't depend on the others, so a batch of them can run side by side;
+//! the lowest one that works is still the answer, whichever thread finishes first
+//! The units within a simulation can't be spread over threads the same way: each one moves (or
+//! picks a target) after seeing what the ones before it did
+//!
+//! `--set threads=N` sets how many threads to use (one per CPU by default); `--set threads=1` runs
+//! the trials one after another, for comparing against
+
+use crate::options;
+use crate::trace;
+use std::panic;
+use std::thread;
+
+/// How many trials to run at once
+pub fn threads() -> usize {
+    options::get("threads")
+        .map(|threads| {
+            threads
+                .parse()
+                .unwrap_or_else(|err| panic!("Unable to parse threads={}: {}", threads, err))
+        })
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()))
+        .max(1)
+}
+
+/// Returns the first number from `from` upwards that `trial` gives a result for, with the result
+/// Never returns if no number works
+pub fn first<R: Send>(from: usize, trial: impl Fn(usize) -> Option<R> + Sync) -> (usize, R) {
+    first_with(threads(), from, trial)
+}
+
+/// Like `first`, trying `threads` numbers at a time
+fn first_with<R: Send>(
+    threads: usize,
+    from: usize,
+    trial: impl Fn(usize) -> Option<R> + Sync,
+) -> (usize, R) {
+    let trial = &trial;
+    let mut batch = from;
+    loop {
+        let _span = trace::span("batch")
+            .arg("from", batch)
+            .arg("threads", threads);
+        let results: Vec<Option<R>> = if threads == 1 {
+            vec![trial(batch)]
+        } else {
+            thread::scope(|scope| {
+                let running: Vec<_> = (batch..batch + threads)
+                    .map(|n| scope.spawn(move || trial(n)))
+                    .collect();
+                running
+                    .into_iter()
+                    .map(|thread| {
+                        thread
+                            .join()
+                            .unwrap_or_else(|err| panic::resume_unwind(err))
+                    })
+                    .collect()
+            })
+        };
+        // The batch's results are in order, so the first that worked is the lowest
+        if let Some((i, result)) = results
+            .into_iter()
+            .enumerate()
+            .find_map(|(i, result)| result.map(|result| (i, result)))
+        {
+            return (batch + i, result);
+        }
+        batch += threads;
+    }
+}
+
+#[test]
+fn test_first() {
+    let trial = |n: usize| if n * n > 50 { Some(n * n) } else { None };
+    for threads in 1..=5 {
+        assert_eq!(
+            first_with(threads, 0, trial),
+            (8, 64),
+            "{} threads",
+            threads
+        );
+        assert_eq!(first_with(threads, 10, trial), (10, 100));
+    }
+}
+
+#[test]
+fn test_first_matches_one_at_a_time() {
+    // Trials that work at random, for random numbers of threads, always give the lowest number
+    // that works
+    let mut rng = crate::generate::Rng::new(7);
+    for _ in 0..50 {
+        // Everything from 40 on works
+        let works: Vec<bool> = (0..40).map(|_| rng.range(0, 10) == 0).collect();
+        let works = |n: usize| works.get(n).cloned().unwrap_or(true);
+        let trial = |n: usize| if works(n) { Some(n * 10) } else { None };
+        let from = rng.range(0, 20);
+        let expected = (from..).find(|&n| works(n)).unwrap();
+        let threads = rng.range(1, 9);
+        assert_eq!(first_with(threads, from, trial), (expected, expected * 10));
+    }
+}
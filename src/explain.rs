@@ -0,0 +1,27 @@
+//! A channel solvers can use to show their working
+//! Nothing is printed unless the runner was started with `--explain`
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set once at startup if the user asked for explanations
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Turns on explanation output for the rest of the run
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Returns true if explanations should be printed
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Prints a line of working (indented under the answer) if `--explain` was given
+/// Takes the same arguments as `println!`, and doesn't format anything when explanations are off
+macro_rules! explain {
+    ($($arg:tt)*) => {
+        if $crate::explain::enabled() {
+            println!("    {}", format!($($arg)*));
+        }
+    };
+}
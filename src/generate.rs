@@ -0,0 +1,130 @@
+//! Generates valid puzzle inputs of any size, for benchmarking and stress testing
+//! Every generator is seeded, so the same seed and size always give the same input
+
+/// A small xorshift random number generator - good enough for making up puzzle inputs
+pub struct Rng(u64);
+
+impl Rng {
+    /// Creates a generator from a seed (any seed works, including 0)
+    pub fn new(seed: u64) -> Rng {
+        // xorshift gets stuck on 0, so spread the seed out and make sure it's odd
+        Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    /// Returns the next random number
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Returns a random number in `low..=high`
+    pub fn range(&mut self, low: usize, high: usize) -> usize {
+        low + (self.next_u64() % (high - low + 1) as u64) as usize
+    }
+}
+
+/// Day 1: `size` frequency adjustments
+/// The adjustments add up to 0, so part 2 is guaranteed to find a repeated frequency
+pub fn day1(rng: &mut Rng, size: usize) -> String {
+    let mut adjustments: Vec<isize> = (1..size).map(|_| rng.range(0, 40) as isize - 20).collect();
+    adjustments.push(-adjustments.iter().sum::<isize>());
+    adjustments
+        .iter()
+        .map(|adjustment| format!("{:+}\n", adjustment))
+        .collect()
+}
+
+/// Day 2: `size` box IDs, exactly one pair of which differs by a single letter
+pub fn day2(rng: &mut Rng, size: usize) -> String {
+    let random_id =
+        |rng: &mut Rng| -> Vec<u8> { (0..26).map(|_| b'a' + rng.range(0, 25) as u8).collect() };
+    let mut ids: Vec<Vec<u8>> = (1..size.max(2)).map(|_| random_id(rng)).collect();
+    // Copy one of the IDs, then change one letter of the copy
+    let mut twin = ids[rng.range(0, ids.len() - 1)].clone();
+    let pos = rng.range(0, twin.len() - 1);
+    twin[pos] = b'a' + (twin[pos] - b'a' + rng.range(1, 25) as u8) % 26;
+    ids.insert(rng.range(0, ids.len()), twin);
+    ids.iter()
+        .map(|id| format!("{}\n", String::from_utf8_lossy(id)))
+        .collect()
+}
+
+/// Day 3: `size` claims that fit on the 1000x1000 inch sheet
+pub fn day3(rng: &mut Rng, size: usize) -> String {
+    (1..=size)
+        .map(|id| {
+            let (width, height) = (rng.range(1, 30), rng.range(1, 30));
+            let (x, y) = (rng.range(0, 1000 - width), rng.range(0, 1000 - height));
+            format!("#{} @ {},{}: {}x{}\n", id, x, y, width, height)
+        })
+        .collect()
+}
+
+/// Returns the (year, month, day) that is `n` days after 1518-01-01, ignoring leap years
+fn date(n: usize) -> (usize, usize, usize) {
+    const DAYS_IN_MONTH: [usize; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+    let (year, mut day_of_year) = (1518 + n / 365, n % 365);
+    let mut month = 0;
+    while day_of_year >= DAYS_IN_MONTH[month] {
+        day_of_year -= DAYS_IN_MONTH[month];
+        month += 1;
+    }
+    (year, month + 1, day_of_year + 1)
+}
+
+/// Day 4: a guard log covering `size` nights, shuffled like the real input
+/// Each night one guard starts a shift around midnight and naps a few times before 1am
+pub fn day4(rng: &mut Rng, size: usize) -> String {
+    let guards: Vec<usize> = (0..(size / 10).max(1))
+        .map(|_| rng.range(1, 3500))
+        .collect();
+    let mut lines = Vec::new();
+    for night in 1..=size {
+        let guard = guards[rng.range(0, guards.len() - 1)];
+        // Shifts start either late the evening before, or just after midnight
+        let (y, m, d) = date(night);
+        let start = if rng.range(0, 1) == 0 {
+            let (y, m, d) = date(night - 1);
+            format!("{}-{:02}-{:02} 23:{:02}", y, m, d, rng.range(45, 59))
+        } else {
+            format!("{}-{:02}-{:02} 00:{:02}", y, m, d, rng.range(0, 5))
+        };
+        lines.push(format!("[{}] Guard #{} begins shift", start, guard));
+        // Naps alternate sleep/wake with strictly increasing minutes
+        let mut minute = rng.range(6, 20);
+        while minute < 55 {
+            let wake = rng.range(minute + 1, (minute + 20).min(59));
+            lines.push(format!(
+                "[{}-{:02}-{:02} 00:{:02}] falls asleep",
+                y, m, d, minute
+            ));
+            lines.push(format!("[{}-{:02}-{:02} 00:{:02}] wakes up", y, m, d, wake));
+            minute = wake + rng.range(1, 15);
+        }
+    }
+    // Shuffle the log (Fisher-Yates)
+    for i in (1..lines.len()).rev() {
+        lines.swap(i, rng.range(0, i));
+    }
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+/// Generates an input for `day`, or None if we have no generator for that day
+pub fn generate(day: usize, size: usize, seed: u64) -> Option<String> {
+    let mut rng = Rng::new(seed);
+    match day {
+        1 => Some(day1(&mut rng, size)),
+        2 => Some(day2(&mut rng, size)),
+        3 => Some(day3(&mut rng, size)),
+        4 => Some(day4(&mut rng, size)),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_same_seed_same_input() {
+    assert_eq!(generate(3, 50, 7), generate(3, 50, 7));
+    assert_ne!(generate(3, 50, 7), generate(3, 50, 8));
+}
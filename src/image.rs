@@ -0,0 +1,147 @@
+//! Just enough of a PNG writer to save the pictures some days can draw (`--set voronoi=...` and
+//! friends), without pulling in a crate
+//! The pixels are stored uncompressed (deflate's "stored" blocks), so the files are big, but any
+//! image viewer can open them
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// A picture made of RGB pixels
+pub struct Image {
+    width: usize,
+    height: usize,
+    /// Row by row from the top left
+    pixels: Vec<[u8; 3]>,
+}
+
+impl Image {
+    /// A black image
+    pub fn new(width: usize, height: usize) -> Image {
+        Image {
+            width,
+            height,
+            pixels: vec![[0; 3]; width * height],
+        }
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, colour: [u8; 3]) {
+        self.pixels[y * self.width + x] = colour;
+    }
+
+    #[cfg(test)]
+    pub fn get(&self, x: usize, y: usize) -> [u8; 3] {
+        self.pixels[y * self.width + x]
+    }
+
+    /// Encodes the image as a PNG file
+    pub fn png(&self) -> Vec<u8> {
+        // Each row starts with the filter it uses: 0 is none
+        let mut raw = Vec::with_capacity((self.width * 3 + 1) * self.height);
+        for row in self.pixels.chunks(self.width.max(1)).take(self.height) {
+            raw.push(0);
+            raw.extend(row.iter().flatten());
+        }
+        // A zlib stream made of stored (uncompressed) deflate blocks
+        let mut zlib = vec![0x78, 0x01];
+        let mut blocks = raw.chunks(0xffff).peekable();
+        if blocks.peek().is_none() {
+            zlib.extend([1, 0, 0, 0xff, 0xff]);
+        }
+        while let Some(block) = blocks.next() {
+            let last = blocks.peek().is_none();
+            let len = block.len() as u16;
+            zlib.push(u8::from(last));
+            zlib.extend(len.to_le_bytes());
+            zlib.extend((!len).to_le_bytes());
+            zlib.extend(block);
+        }
+        zlib.extend(adler32(&raw).to_be_bytes());
+
+        let mut header = Vec::new();
+        header.extend((self.width as u32).to_be_bytes());
+        header.extend((self.height as u32).to_be_bytes());
+        // 8 bits per channel, RGB, then the default compression, filtering and no interlacing
+        header.extend([8, 2, 0, 0, 0]);
+
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        chunk(&mut png, b"IHDR", &header);
+        chunk(&mut png, b"IDAT", &zlib);
+        chunk(&mut png, b"IEND", &[]);
+        png
+    }
+
+    /// Saves the image as a PNG file
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.png())
+    }
+}
+
+/// Appends a PNG chunk: its length, type, data and checksum
+fn chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    png.extend(kind);
+    png.extend(data);
+    png.extend(crc32(&[&kind[..], data].concat()).to_be_bytes());
+}
+
+/// The CRC that PNG chunks end with
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0, |crc: u32, &byte| {
+        (0..8).fold(crc ^ u32::from(byte), |crc, _| {
+            if crc & 1 == 1 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
+
+/// The checksum at the end of a zlib stream
+fn adler32(data: &[u8]) -> u32 {
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), &byte| {
+        let a = (a + u32::from(byte)) % 65521;
+        (a, (b + a) % 65521)
+    });
+    b << 16 | a
+}
+
+/// Picks a bright colour for the `n`th thing in a picture, stepping round the colour wheel by the
+/// golden angle so that neighbouring numbers look different
+pub fn colour(n: usize) -> [u8; 3] {
+    let hue = (n as f64 * 137.508) % 360.0 / 60.0;
+    let x = 1.0 - (hue % 2.0 - 1.0).abs();
+    let (r, g, b) = match hue as usize {
+        0 => (1.0, x, 0.0),
+        1 => (x, 1.0, 0.0),
+        2 => (0.0, 1.0, x),
+        3 => (0.0, x, 1.0),
+        4 => (x, 0.0, 1.0),
+        _ => (1.0, 0.0, x),
+    };
+    // Softened towards white, so the pictures aren't garish
+    let channel = |c: f64| (255.0 * (0.35 + 0.65 * c)) as u8;
+    [channel(r), channel(g), channel(b)]
+}
+
+#[test]
+fn test_checksums() {
+    // The CRC of the IEND chunk, which is the same in every PNG
+    assert_eq!(crc32(b"IEND"), 0xae42_6082);
+    assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+}
+
+#[test]
+fn test_png() {
+    let mut image = Image::new(2, 1);
+    image.set(1, 0, [255, 0, 0]);
+    let png = image.png();
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    assert_eq!(&png[12..16], b"IHDR");
+    // The pixel data, after the zlib header and the stored block's header
+    let idat = 8 + 25;
+    assert_eq!(&png[idat + 4..idat + 8], b"IDAT");
+    assert_eq!(&png[idat + 8 + 7..idat + 8 + 14], [0, 0, 0, 0, 255, 0, 0]);
+    assert!(png.ends_with(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]));
+}
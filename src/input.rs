@@ -0,0 +1,424 @@
+//! Finds and reads the puzzle input for each day
+//! The input can live in any of several directories, so the crate works with different repo
+//! layouts (eg. inputs kept in a private submodule), or be named on the command line with --input
+//!
+//! Encrypted and compressed inputs are handed to external programs, which must be on the PATH:
+//! `gpg` for `.gpg` files, and `gzip` or `zstd` for files that start with their magic bytes
+//! (whatever their extension). If the program is missing or fails, `read_input` returns
+//! `InputError::Tool` naming it, rather than panicking
+
+use crate::wide::Overflow;
+use std::env;
+use std::error::Error;
+use std::ffi::OsStr;
+use std::fmt;
+use std::fs::read;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+
+/// Environment variable pointing at an extra directory to search first
+const INPUT_DIR_VAR: &str = "AOC_INPUT_DIR";
+
+/// Environment variable holding the passphrase for encrypted (`.gpg`) inputs
+const INPUT_KEY_VAR: &str = "AOC_INPUT_KEY";
+
+/// What `read_input` reads instead of the day's own input
+enum Override {
+    /// An example built into the binary, set by --example
+    Text(&'static str),
+    /// A file named on the command line, set by --input
+    File(PathBuf),
+}
+
+/// The input to use instead of the real one, if any
+static OVERRIDE: Mutex<Option<Override>> = Mutex::new(None);
+
+/// Directories searched (in order) after `$AOC_INPUT_DIR`
+const DEFAULT_DIRS: [&str; 2] = ["data", "inputs"];
+
+/// Returns the directories to search for inputs, in priority order
+fn search_dirs() -> Vec<PathBuf> {
+    env::var_os(INPUT_DIR_VAR)
+        .map(PathBuf::from)
+        .into_iter()
+        .chain(DEFAULT_DIRS.iter().map(PathBuf::from))
+        .collect()
+}
+
+/// Returns the file names a day's input may be stored under, in priority order
+/// eg. day 3 could be in day3.txt, day03.txt or 3.txt
+/// Each name is followed by its encrypted and compressed versions (eg. day3.txt.gpg, day3.txt.gz)
+fn file_names(day: usize) -> Vec<String> {
+    let mut names = vec![
+        format!("day{}.txt", day),
+        format!("day{:02}.txt", day),
+        format!("{}.txt", day),
+    ];
+    // From day 10 on, the padded name is the same as the plain one
+    names.dedup();
+    names
+        .into_iter()
+        .flat_map(|name| {
+            ["", ".gpg", ".gz", ".zst"]
+                .iter()
+                .map(move |suffix| format!("{}{}", name, suffix))
+        })
+        .collect()
+}
+
+/// Returns every path that could hold the input for `day`, in the order they should be tried
+fn candidates(dirs: &[PathBuf], day: usize) -> Vec<PathBuf> {
+    dirs.iter()
+        .flat_map(|dir| file_names(day).into_iter().map(move |name| dir.join(name)))
+        .collect()
+}
+
+/// None of the places we look for a day's input had it
+#[derive(Debug, PartialEq)]
+pub struct MissingInput {
+    /// The day whose input is missing
+    pub day: usize,
+    /// Every path we looked in, in the order we tried them
+    pub tried_paths: Vec<PathBuf>,
+}
+
+impl fmt::Display for MissingInput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "No input found for day {}. Looked in:", self.day)?;
+        for path in &self.tried_paths {
+            writeln!(f, "    {}", path.display())?;
+        }
+        write!(
+            f,
+            "Save https://adventofcode.com/2018/day/{}/input as data/day{}.txt \
+             (or point ${} at the directory holding it, or pass --input <file>)",
+            self.day, self.day, INPUT_DIR_VAR
+        )
+    }
+}
+
+impl Error for MissingInput {}
+
+/// Why a day's input couldn't be read
+#[derive(Debug)]
+pub enum InputError {
+    /// There's no input in any of the places we look
+    Missing(MissingInput),
+    /// The input file is there, but reading it failed
+    Unreadable { path: PathBuf, error: io::Error },
+    /// The input (after decrypting or decompressing it) isn't UTF-8 text
+    NotUtf8 { path: PathBuf },
+    /// The program we use to decrypt or decompress the input couldn't be run, or failed
+    Tool {
+        program: &'static str,
+        path: PathBuf,
+        reason: String,
+    },
+    /// A solver's accumulator overflowed while working on the input (see `wide`)
+    Overflow(Overflow),
+    /// Saving something a day drew (eg. a picture asked for with `--set`) failed
+    Unwritable { path: PathBuf, error: io::Error },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputError::Missing(missing) => missing.fmt(f),
+            InputError::Unreadable { path, error } => {
+                write!(f, "Unable to read {}: {}", path.display(), error)
+            }
+            InputError::NotUtf8 { path } => write!(f, "{} isn't UTF-8 text", path.display()),
+            InputError::Tool {
+                program,
+                path,
+                reason,
+            } => write!(
+                f,
+                "{} couldn't read {}: {}",
+                program,
+                path.display(),
+                reason
+            ),
+            InputError::Overflow(overflow) => overflow.fmt(f),
+            InputError::Unwritable { path, error } => {
+                write!(f, "Unable to write {}: {}", path.display(), error)
+            }
+        }
+    }
+}
+
+impl Error for InputError {}
+
+impl From<MissingInput> for InputError {
+    fn from(missing: MissingInput) -> InputError {
+        InputError::Missing(missing)
+    }
+}
+
+impl From<Overflow> for InputError {
+    fn from(overflow: Overflow) -> InputError {
+        InputError::Overflow(overflow)
+    }
+}
+
+/// Returns the first path in `dirs` that holds the input for `day`
+fn find_input(dirs: &[PathBuf], day: usize) -> Result<PathBuf, MissingInput> {
+    let tried_paths = candidates(dirs, day);
+    match tried_paths.iter().find(|path| Path::is_file(path)) {
+        Some(path) => Ok(path.clone()),
+        None => Err(MissingInput { day, tried_paths }),
+    }
+}
+
+/// Returns the path of the input for `day`
+pub fn locate_input(day: usize) -> Result<PathBuf, MissingInput> {
+    find_input(&search_dirs(), day)
+}
+
+/// Decrypts a gpg encrypted input
+/// Uses the passphrase in `$AOC_INPUT_KEY` if it's set, otherwise leaves gpg to find the key
+fn decrypt(path: &Path) -> Result<Vec<u8>, InputError> {
+    let tool_error = |reason: String| InputError::Tool {
+        program: "gpg",
+        path: path.to_path_buf(),
+        reason,
+    };
+    let passphrase = env::var(INPUT_KEY_VAR).ok();
+    let mut gpg = Command::new("gpg");
+    gpg.args(["--batch", "--quiet", "--decrypt"]);
+    if passphrase.is_some() {
+        gpg.args(["--pinentry-mode", "loopback", "--passphrase-fd", "0"]);
+    }
+    let mut child = gpg
+        .arg(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|err| tool_error(err.to_string()))?;
+    if let Some(passphrase) = passphrase {
+        // gpg reads the passphrase from stdin, which is closed again at the end of this block
+        let mut stdin = child.stdin.take().unwrap();
+        stdin
+            .write_all(passphrase.as_bytes())
+            .map_err(|err| tool_error(err.to_string()))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|err| tool_error(err.to_string()))?;
+    if !output.status.success() {
+        return Err(tool_error(format!("it exited with {}", output.status)));
+    }
+    Ok(output.stdout)
+}
+
+/// Compression formats we can read: the magic bytes a compressed file starts with, and the
+/// program that decompresses it
+const COMPRESSED: [(&[u8], &str); 2] =
+    [(&[0x1f, 0x8b], "gzip"), (&[0x28, 0xb5, 0x2f, 0xfd], "zstd")];
+
+/// Returns the program to decompress `bytes` with, or None if they aren't compressed
+/// Goes by the content rather than the extension, so a compressed file saved as .txt still works
+fn decompressor(bytes: &[u8]) -> Option<&'static str> {
+    COMPRESSED
+        .iter()
+        .find(|(magic, _)| bytes.starts_with(magic))
+        .map(|(_, program)| *program)
+}
+
+/// Decompresses a gzip or zstd compressed input with `program`
+fn decompress(program: &'static str, path: &Path) -> Result<Vec<u8>, InputError> {
+    let tool_error = |reason: String| InputError::Tool {
+        program,
+        path: path.to_path_buf(),
+        reason,
+    };
+    let output = Command::new(program)
+        .args(["--decompress", "--stdout"])
+        .arg(path)
+        .output()
+        .map_err(|err| tool_error(err.to_string()))?;
+    if !output.status.success() {
+        return Err(tool_error(format!("it exited with {}", output.status)));
+    }
+    Ok(output.stdout)
+}
+
+/// Cleans up the differences a text editor can introduce into an input, so the parsers don't have
+/// to: strips a UTF-8 byte order mark, trailing whitespace on each line, and blank lines at the
+/// end, and turns `\r\n` and lone `\r` (old Mac) line endings into `\n`. Every line (including the
+/// last) ends in a single `\n`
+pub fn normalize(text: &str) -> String {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let text = text.replace("\r\n", "\n");
+    let lines: Vec<&str> = text.split(['\r', '\n']).map(str::trim_end).collect();
+    let end = lines
+        .iter()
+        .rposition(|line| !line.is_empty())
+        .map_or(0, |last| last + 1);
+    lines[..end]
+        .iter()
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+/// Makes `read_input` return `example` instead of the real input, for the rest of the run
+pub fn use_example(example: &'static str) {
+    *OVERRIDE.lock().unwrap() = Some(Override::Text(example));
+}
+
+/// Makes `read_input` read `path` instead of the real input, for the rest of the run
+pub fn use_file(path: PathBuf) {
+    *OVERRIDE.lock().unwrap() = Some(Override::File(path));
+}
+
+/// Reads an input file, decrypting or decompressing it if needed
+fn read_file(path: &Path) -> Result<String, InputError> {
+    let bytes = if path.extension() == Some(OsStr::new("gpg")) {
+        decrypt(path)?
+    } else {
+        let bytes = read(path).map_err(|error| InputError::Unreadable {
+            path: path.to_path_buf(),
+            error,
+        })?;
+        match decompressor(&bytes) {
+            Some(program) => decompress(program, path)?,
+            None => bytes,
+        }
+    };
+    String::from_utf8(bytes).map_err(|_| InputError::NotUtf8 {
+        path: path.to_path_buf(),
+    })
+}
+
+/// Reads the whole input for `day` (or the file given with --input), decrypting, decompressing
+/// and normalizing it if needed
+pub fn read_input(day: usize) -> Result<String, InputError> {
+    let text = match &*OVERRIDE.lock().unwrap() {
+        Some(Override::Text(example)) => example.to_string(),
+        Some(Override::File(path)) => read_file(path)?,
+        None => read_file(&locate_input(day)?)?,
+    };
+    let text = normalize(&text);
+    crate::stopwatch::lap("read");
+    Ok(text)
+}
+
+#[test]
+fn test_candidates_order() {
+    let dirs = vec![PathBuf::from("a"), PathBuf::from("b")];
+    let paths = candidates(&dirs, 7);
+    assert_eq!(paths.len(), 24);
+    assert_eq!(
+        paths[..8],
+        [
+            PathBuf::from("a/day7.txt"),
+            PathBuf::from("a/day7.txt.gpg"),
+            PathBuf::from("a/day7.txt.gz"),
+            PathBuf::from("a/day7.txt.zst"),
+            PathBuf::from("a/day07.txt"),
+            PathBuf::from("a/day07.txt.gpg"),
+            PathBuf::from("a/day07.txt.gz"),
+            PathBuf::from("a/day07.txt.zst"),
+        ]
+    );
+    assert_eq!(paths[8], PathBuf::from("a/7.txt"));
+    assert_eq!(paths[12], PathBuf::from("b/day7.txt"));
+    assert_eq!(paths[23], PathBuf::from("b/7.txt.zst"));
+}
+
+#[test]
+fn test_find_input() {
+    // The committed inputs live in ./data
+    let dirs = vec![PathBuf::from("missing"), PathBuf::from("data")];
+    assert_eq!(find_input(&dirs, 1), Ok(PathBuf::from("data/day1.txt")));
+}
+
+#[test]
+fn test_missing_input_lists_tried_paths() {
+    let dirs = vec![PathBuf::from("data")];
+    let err = find_input(&dirs, 99).unwrap_err();
+    assert_eq!(
+        err,
+        MissingInput {
+            day: 99,
+            tried_paths: vec![
+                PathBuf::from("data/day99.txt"),
+                PathBuf::from("data/day99.txt.gpg"),
+                PathBuf::from("data/day99.txt.gz"),
+                PathBuf::from("data/day99.txt.zst"),
+                PathBuf::from("data/99.txt"),
+                PathBuf::from("data/99.txt.gpg"),
+                PathBuf::from("data/99.txt.gz"),
+                PathBuf::from("data/99.txt.zst"),
+            ],
+        }
+    );
+    assert!(err.to_string().contains("day/99/input"));
+}
+
+#[test]
+fn test_normalize() {
+    // A day 1 input saved by a Windows editor
+    let input = "\u{feff}+1\r\n-2  \r\n+3\t\r\n\r\n\n";
+    assert_eq!(normalize(input), "+1\n-2\n+3\n");
+    // Leading whitespace matters in some grids, so it stays
+    assert_eq!(normalize("  /-\\\n  \\-/"), "  /-\\\n  \\-/\n");
+    assert_eq!(normalize("\n\n"), "");
+    // Old Mac line endings, alone or mixed with the others
+    assert_eq!(normalize("+1\r-2\r+3\r"), "+1\n-2\n+3\n");
+    assert_eq!(normalize("+1\r\n-2\r+3\n"), "+1\n-2\n+3\n");
+}
+
+#[test]
+fn test_decompressor() {
+    assert_eq!(decompressor(&[0x1f, 0x8b, 0x08, 0x00]), Some("gzip"));
+    assert_eq!(decompressor(&[0x28, 0xb5, 0x2f, 0xfd, 0x04]), Some("zstd"));
+    assert_eq!(decompressor(b"+1\n-2\n"), None);
+    assert_eq!(decompressor(&[0x1f]), None);
+}
+
+#[test]
+fn test_read_file_errors() {
+    let path = Path::new("missing/day1.txt");
+    assert!(matches!(
+        read_file(path),
+        Err(InputError::Unreadable { .. })
+    ));
+    // The committed inputs are all text
+    assert!(read_file(Path::new("data/day1.txt")).is_ok());
+}
+
+#[test]
+fn test_overflow_error() {
+    let overflow = Overflow {
+        day: 12,
+        value: 5,
+        op: '*',
+        operand: 7,
+    };
+    let err = InputError::from(overflow.clone());
+    assert!(matches!(&err, InputError::Overflow(inner) if *inner == overflow));
+    assert_eq!(err.to_string(), overflow.to_string());
+}
+
+#[test]
+fn test_read_gzip_file() {
+    // Needs gzip itself to make the compressed file
+    let path = env::temp_dir().join(format!("aoc-test-{}.txt", std::process::id()));
+    std::fs::write(&path, "+1\n-2\n+3\n").unwrap();
+    let status = Command::new("gzip").arg("--force").arg(&path).status();
+    if !status.is_ok_and(|status| status.success()) {
+        eprintln!("Skipping the gzip round trip: gzip isn't available");
+        return;
+    }
+    let compressed = path.with_extension("txt.gz");
+    let text = read_file(&compressed);
+    // Compressed files are recognised by their content, so the extension doesn't matter
+    std::fs::rename(&compressed, &path).unwrap();
+    let renamed = read_file(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(text.unwrap(), "+1\n-2\n+3\n");
+    assert_eq!(renamed.unwrap(), "+1\n-2\n+3\n");
+}
@@ -1,13 +1,670 @@
+#[macro_use]
+mod explain;
+
+#[cfg(feature = "peak-memory")]
+mod alloc;
+mod animate;
+mod automaton;
+mod compare;
 mod day1;
+mod day10;
+mod day11;
+mod day12;
+mod day13;
+mod day14;
+mod day15;
+mod day16;
+mod day17;
+mod day18;
+mod day19;
 mod day2;
+mod day20;
+mod day21;
+mod day22;
+mod day23;
+mod day24;
 mod day3;
 mod day4;
+mod day5;
+mod day6;
+mod day7;
+mod day8;
+mod day9;
+mod elfcode;
+mod executor;
+mod generate;
+mod image;
+mod input;
+mod options;
+mod stopwatch;
+mod trace;
+mod wide;
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::exit;
+
+#[cfg(feature = "peak-memory")]
+#[global_allocator]
+static ALLOCATOR: alloc::CountingAlloc = alloc::CountingAlloc;
+
+/// Prints the answer to one part of a puzzle, failing if its input can't be read
+type Part = fn() -> Result<(), input::InputError>;
+
+/// Everything we know about one day's puzzle
+struct Day {
+    /// Day of the month the puzzle was released
+    number: usize,
+    /// The puzzle's title
+    title: &'static str,
+    /// Prints the answer to part 1 (if we've solved it)
+    part1: Option<Part>,
+    /// Prints the answer to part 2 (if we've solved it)
+    part2: Option<Part>,
+    /// True if the solver is tested against the puzzle's worked example
+    example_test: bool,
+    /// The example inputs from the puzzle text, run with --example
+    examples: &'static [&'static str],
+    /// Inputs the solver takes besides the puzzle input
+    parameters: &'static str,
+    /// Cross-checks between the two parts' results, run by --verify-invariants
+    invariants: Option<fn() -> Result<(), String>>,
+    /// The setting that picks between ways of solving the day, and its values, for `compare`
+    strategies: Option<(&'static str, &'static [&'static str])>,
+}
+
+/// Every day that has been started
+const DAYS: &[Day] = &[
+    Day {
+        number: 1,
+        title: "Chronal Calibration",
+        part1: Some(day1::part1),
+        part2: Some(day1::part2),
+        example_test: true,
+        examples: day1::EXAMPLES,
+        parameters: "",
+        invariants: None,
+        strategies: None,
+    },
+    Day {
+        number: 2,
+        title: "Inventory Management System",
+        part1: Some(day2::part1),
+        part2: Some(day2::part2),
+        example_test: false,
+        examples: day2::EXAMPLES,
+        parameters: "",
+        invariants: None,
+        strategies: None,
+    },
+    Day {
+        number: 3,
+        title: "No Matter How You Slice It",
+        part1: Some(day3::part1),
+        part2: Some(day3::part2),
+        example_test: true,
+        examples: day3::EXAMPLES,
+        parameters: "--set sheet=hashmap|quadtree, heatmap=file.png",
+        invariants: Some(day3::verify_invariants),
+        strategies: Some(("sheet", &["hashmap", "quadtree"])),
+    },
+    Day {
+        number: 4,
+        title: "Repose Record",
+        part1: Some(day4::part1),
+        part2: Some(day4::part2),
+        example_test: true,
+        examples: day4::EXAMPLES,
+        parameters: "--set at=1518-11-01T00:05, ics=file.ics, heatmap",
+        invariants: Some(day4::verify_invariants),
+        strategies: None,
+    },
+    Day {
+        number: 5,
+        title: "Alchemical Reduction",
+        part1: Some(day5::part1),
+        part2: Some(day5::part2),
+        example_test: true,
+        examples: day5::EXAMPLES,
+        parameters: "",
+        invariants: None,
+        strategies: None,
+    },
+    Day {
+        number: 6,
+        title: "Chronal Coordinates",
+        part1: Some(day6::part1),
+        part2: Some(day6::part2),
+        example_test: true,
+        examples: day6::EXAMPLES,
+        parameters:
+            "total distance < 10000; --set metric=manhattan|chebyshev|euclidean, voronoi=file.png",
+        invariants: None,
+        strategies: None,
+    },
+    Day {
+        number: 7,
+        title: "The Sum of Its Parts",
+        part1: Some(day7::part1),
+        part2: Some(day7::part2),
+        example_test: true,
+        examples: day7::EXAMPLES,
+        parameters: "5 workers, steps take 60s + letter; --set timeline, dot",
+        invariants: None,
+        strategies: None,
+    },
+    Day {
+        number: 8,
+        title: "Memory Maneuver",
+        part1: Some(day8::part1),
+        part2: Some(day8::part2),
+        example_test: true,
+        examples: day8::EXAMPLES,
+        parameters: "",
+        invariants: None,
+        strategies: None,
+    },
+    Day {
+        number: 9,
+        title: "Marble Mania",
+        part1: Some(day9::part1),
+        part2: Some(day9::part2),
+        example_test: true,
+        examples: day9::EXAMPLES,
+        parameters: "--set special=23, removal-offset=7, scoring=both|kept|removed",
+        invariants: None,
+        strategies: None,
+    },
+    Day {
+        number: 10,
+        title: "The Stars Align",
+        part1: Some(day10::part1),
+        part2: Some(day10::part2),
+        example_test: true,
+        examples: day10::EXAMPLES,
+        parameters: "--set visualize[=N], delay=ms",
+        invariants: None,
+        strategies: None,
+    },
+    Day {
+        number: 11,
+        title: "Chronal Charge",
+        part1: Some(day11::part1),
+        part2: Some(day11::part2),
+        example_test: true,
+        examples: day11::EXAMPLES,
+        parameters: "300x300 grid; --set grid-size=N",
+        invariants: None,
+        strategies: None,
+    },
+    Day {
+        number: 12,
+        title: "Subterranean Sustainability",
+        part1: Some(day12::part1),
+        part2: Some(day12::part2),
+        example_test: true,
+        examples: day12::EXAMPLES,
+        parameters: "",
+        invariants: None,
+        strategies: None,
+    },
+    Day {
+        number: 13,
+        title: "Mine Cart Madness",
+        part1: Some(day13::part1),
+        part2: Some(day13::part2),
+        example_test: true,
+        examples: day13::EXAMPLES,
+        parameters: "--set animate, delay=ms",
+        invariants: None,
+        strategies: None,
+    },
+    Day {
+        number: 14,
+        title: "Chocolate Charts",
+        part1: Some(day14::part1),
+        part2: Some(day14::part2),
+        example_test: true,
+        examples: day14::EXAMPLES,
+        parameters: "",
+        invariants: None,
+        strategies: None,
+    },
+    Day {
+        number: 15,
+        title: "Beverage Bandits",
+        part1: Some(day15::part1),
+        part2: Some(day15::part2),
+        example_test: true,
+        examples: day15::EXAMPLES,
+        parameters: "--set replay, delay=ms, threads=N",
+        invariants: None,
+        strategies: None,
+    },
+    Day {
+        number: 16,
+        title: "Chronal Classification",
+        part1: Some(day16::part1),
+        part2: Some(day16::part2),
+        example_test: true,
+        examples: day16::EXAMPLES,
+        parameters: "",
+        invariants: None,
+        strategies: None,
+    },
+    Day {
+        number: 17,
+        title: "Reservoir Research",
+        part1: Some(day17::part1),
+        part2: Some(day17::part2),
+        example_test: true,
+        examples: day17::EXAMPLES,
+        parameters: "--set render[=N], delay=ms",
+        invariants: None,
+        strategies: None,
+    },
+    Day {
+        number: 18,
+        title: "Settlers of The North Pole",
+        part1: Some(day18::part1),
+        part2: Some(day18::part2),
+        example_test: true,
+        examples: day18::EXAMPLES,
+        parameters: "--set live, delay=ms",
+        invariants: None,
+        strategies: None,
+    },
+    Day {
+        number: 19,
+        title: "Go With The Flow",
+        part1: Some(day19::part1),
+        part2: Some(day19::part2),
+        example_test: true,
+        examples: day19::EXAMPLES,
+        parameters: "",
+        invariants: Some(day19::verify_invariants),
+        strategies: None,
+    },
+    Day {
+        number: 20,
+        title: "A Regular Map",
+        part1: Some(day20::part1),
+        part2: Some(day20::part2),
+        example_test: true,
+        examples: day20::EXAMPLES,
+        parameters: "--set map",
+        invariants: None,
+        strategies: None,
+    },
+    Day {
+        number: 21,
+        title: "Chronal Conversion",
+        part1: Some(day21::part1),
+        part2: Some(day21::part2),
+        example_test: false,
+        examples: &[],
+        parameters: "--set decompile, vm",
+        invariants: Some(day21::verify_invariants),
+        strategies: None,
+    },
+    Day {
+        number: 22,
+        title: "Mode Maze",
+        part1: Some(day22::part1),
+        part2: Some(day22::part2),
+        example_test: true,
+        examples: day22::EXAMPLES,
+        parameters: "--set route",
+        invariants: None,
+        strategies: None,
+    },
+    Day {
+        number: 23,
+        title: "Experimental Emergency Teleportation",
+        part1: Some(day23::part1),
+        part2: Some(day23::part2),
+        example_test: true,
+        examples: day23::EXAMPLES,
+        parameters: "",
+        invariants: None,
+        strategies: None,
+    },
+    Day {
+        number: 24,
+        title: "Immune System Simulator 20XX",
+        part1: Some(day24::part1),
+        part2: Some(day24::part2),
+        example_test: true,
+        examples: day24::EXAMPLES,
+        parameters: "--set threads=N",
+        invariants: None,
+        strategies: None,
+    },
+];
+
+/// Runs a single part, reporting how long each of its stages took if `timings` is set, and its
+/// peak memory use if we're counting allocations
+/// Returns false (having said why) if the part couldn't read its input
+fn run_part(part: Part, timings: bool) -> bool {
+    #[cfg(feature = "peak-memory")]
+    alloc::reset_peak();
+    stopwatch::start();
+    let result = part();
+    let stages = stopwatch::finish();
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        return false;
+    }
+    if timings {
+        let stages: Vec<String> = stages
+            .iter()
+            .map(|(stage, time)| format!("{}: {:.2?}", stage, time))
+            .collect();
+        println!("    ({})", stages.join(", "));
+    }
+    #[cfg(feature = "peak-memory")]
+    println!("    (peak memory: {} KiB)", alloc::peak() / 1024);
+    true
+}
+
+/// Runs every part we have a solution for (or only those for `only_day`), skipping days without
+/// an input
+/// A whole calendar run gives one line per missing input; asking for a single day shows every
+/// path we looked in
+fn run_all(timings: bool, only_day: Option<usize>) {
+    for day in DAYS
+        .iter()
+        .filter(|day| only_day.is_none_or(|number| number == day.number))
+    {
+        if day.part1.is_none() && day.part2.is_none() {
+            continue;
+        }
+        match (input::locate_input(day.number), only_day) {
+            (Ok(_), _) => {
+                run_day(day, timings);
+            }
+            (Err(missing), Some(_)) => eprintln!("{}", missing),
+            (Err(_), None) => eprintln!(
+                "Day {}: no input (run with --day {} to see where we looked)",
+                day.number, day.number
+            ),
+        }
+    }
+}
+
+/// Returns the day numbered `number`, exiting if it hasn't been started
+fn find_day(number: usize) -> &'static Day {
+    DAYS.iter()
+        .find(|day| day.number == number)
+        .unwrap_or_else(|| {
+            eprintln!("Day {} hasn't been started", number);
+            exit(1)
+        })
+}
+
+/// Runs both parts of a day (after its input has been chosen)
+/// Stops at the first part that can't read its input, returning false
+fn run_day(day: &Day, timings: bool) -> bool {
+    let _span = trace::span("day").arg("day", day.number);
+    let parts = day.part1.map(|part| (1, part));
+    parts
+        .into_iter()
+        .chain(day.part2.map(|part| (2, part)))
+        .all(|(number, part)| {
+            let _span = trace::span("part")
+                .arg("day", day.number)
+                .arg("part", number);
+            run_part(part, timings)
+        })
+}
+
+/// Runs a day against one of the examples from its puzzle text (numbered from 1)
+fn run_example(number: usize, example: usize, timings: bool) {
+    let day = find_day(number);
+    match example.checked_sub(1).and_then(|i| day.examples.get(i)) {
+        Some(text) => input::use_example(text),
+        None => {
+            eprintln!("Day {} has {} example(s)", number, day.examples.len());
+            exit(1);
+        }
+    }
+    if !run_day(day, timings) {
+        exit(1);
+    }
+}
+
+/// Runs a day against the input in `path`, rather than the one in the usual places
+fn run_input(number: usize, path: &str, timings: bool) {
+    let day = find_day(number);
+    if !Path::new(path).is_file() {
+        eprintln!("No such input file: {}", path);
+        exit(1);
+    }
+    input::use_file(PathBuf::from(path));
+    if !run_day(day, timings) {
+        exit(1);
+    }
+}
+
+/// Runs every day's cross-part checks, returning false if any of them failed
+fn verify_invariants() -> bool {
+    let mut all_ok = true;
+    for day in DAYS {
+        if let Some(invariants) = day.invariants {
+            if let Err(missing) = input::locate_input(day.number) {
+                eprintln!("{}", missing);
+                continue;
+            }
+            match invariants() {
+                Ok(()) => println!("Day {} invariants: ok", day.number),
+                Err(err) => {
+                    println!("Day {} invariants: FAILED: {}", day.number, err);
+                    all_ok = false;
+                }
+            }
+        }
+    }
+    all_ok
+}
+
+/// Prints a status table of the whole calendar
+fn list() {
+    let yes_no = |b: bool| if b { "yes" } else { "no" };
+    println!(
+        "{:>3}  {:<40} {:<6} {:<6} {:<7} Parameters",
+        "Day", "Title", "Part 1", "Part 2", "Example"
+    );
+    for number in 1..=25 {
+        match DAYS.iter().find(|day| day.number == number) {
+            Some(day) => println!(
+                "{:>3}  {:<40} {:<6} {:<6} {:<7} {}",
+                day.number,
+                day.title,
+                yes_no(day.part1.is_some()),
+                yes_no(day.part2.is_some()),
+                yes_no(day.example_test),
+                day.parameters
+            ),
+            None => println!(
+                "{:>3}  {:<40} {:<6} {:<6} {:<7}",
+                number, "-", "no", "no", "no"
+            ),
+        }
+    }
+}
+
+/// Prints a synthetic input: generate <day> [size] [seed]
+fn generate(args: &[String]) {
+    let number = |i: usize, default: usize| -> usize {
+        args.get(i).map_or(default, |arg| {
+            arg.parse().unwrap_or_else(|_| {
+                eprintln!("Expected a number, got: {}", arg);
+                exit(1)
+            })
+        })
+    };
+    let (day, size, seed) = (number(0, 0), number(1, 1000), number(2, 0));
+    match generate::generate(day, size, seed as u64) {
+        Some(input) => print!("{}", input),
+        None => {
+            eprintln!("Usage: generate <day> [size] [seed] (days 1-4 have generators)");
+            exit(1);
+        }
+    }
+}
+
+/// Removes `flag` from `args`, returning true if it was there
+/// This lets flags go anywhere on the command line
+fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    match args.iter().position(|arg| arg == flag) {
+        Some(pos) => {
+            args.remove(pos);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Removes `flag` and the argument following it from `args`, returning the argument
+fn take_value(args: &mut Vec<String>, flag: &str) -> Option<String> {
+    let pos = args.iter().position(|arg| arg == flag)?;
+    args.remove(pos);
+    if pos < args.len() {
+        Some(args.remove(pos))
+    } else {
+        eprintln!("{} needs a value after it", flag);
+        exit(1)
+    }
+}
+
+/// Removes `flag` and the number following it from `args`
+/// Returns None if the flag isn't there, or `default` if no number follows it
+fn take_number(args: &mut Vec<String>, flag: &str, default: Option<usize>) -> Option<usize> {
+    let pos = args.iter().position(|arg| arg == flag)?;
+    args.remove(pos);
+    match args.get(pos).and_then(|arg| arg.parse().ok()) {
+        Some(number) => {
+            args.remove(pos);
+            Some(number)
+        }
+        None => default.or_else(|| {
+            eprintln!("{} needs a number after it", flag);
+            exit(1)
+        }),
+    }
+}
 
 fn main() {
-    day1::part1();
-    day1::part2();
-    day2::part1();
-    day2::part2();
-    day3::part1();
-    day3::part2();
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    if take_flag(&mut args, "--explain") {
+        explain::enable();
+    }
+    let timings = take_flag(&mut args, "--timings");
+    while let Some(setting) = take_value(&mut args, "--set") {
+        options::set(&setting);
+    }
+    let day = take_number(&mut args, "--day", None);
+    let example = take_number(&mut args, "--example", Some(1));
+    let input = take_value(&mut args, "--input");
+    let inputs = take_value(&mut args, "--inputs");
+    let trace = take_value(&mut args, "--trace");
+    if trace.is_some() && !cfg!(feature = "trace") {
+        eprintln!("--trace needs the trace feature (cargo run --features trace)");
+        exit(1);
+    }
+    match args.first().map(String::as_str) {
+        None if inputs.is_some() => {
+            let dir = inputs.unwrap();
+            match (day, example, input) {
+                (Some(day), None, None) => {
+                    if let Err(err) = compare::compare_answers(day, &dir) {
+                        eprintln!("{}", err);
+                        exit(1);
+                    }
+                }
+                _ => {
+                    eprintln!(
+                        "--inputs needs a --day, and can't be used with --example or --input"
+                    );
+                    exit(1);
+                }
+            }
+        }
+        None => match (day, example, input) {
+            (_, Some(_), Some(_)) => {
+                eprintln!("--example and --input can't be used together");
+                exit(1);
+            }
+            (Some(day), Some(example), None) => run_example(day, example, timings),
+            (Some(day), None, Some(input)) => run_input(day, &input, timings),
+            (None, Some(_), None) | (None, None, Some(_)) => {
+                eprintln!("--example and --input need a --day to run");
+                exit(1);
+            }
+            (day, None, None) => run_all(timings, day),
+        },
+        Some("list") => list(),
+        Some("generate") => generate(&args[1..]),
+        Some("compare") => {
+            // Pass the choice of input on to each run
+            let input: Vec<String> = match (example, input) {
+                (Some(example), None) => vec!["--example".to_string(), example.to_string()],
+                (None, Some(input)) => vec!["--input".to_string(), input],
+                _ => Vec::new(),
+            };
+            match day
+                .map(find_day)
+                .and_then(|day| Some((day.number, day.strategies?)))
+            {
+                Some((day, (setting, values))) => {
+                    if let Err(err) = compare::compare_strategies(day, setting, values, &input) {
+                        eprintln!("{}", err);
+                        exit(1);
+                    }
+                }
+                None => {
+                    let days: Vec<String> = DAYS
+                        .iter()
+                        .filter(|day| day.strategies.is_some())
+                        .map(|day| day.number.to_string())
+                        .collect();
+                    eprintln!(
+                        "Usage: compare --day <day> (days with more than one strategy: {})",
+                        days.join(", ")
+                    );
+                    exit(1);
+                }
+            }
+        }
+        Some("compare-inputs") => match &args[1..] {
+            [a, b] => {
+                if let Err(err) = compare::compare_inputs(a, b) {
+                    eprintln!("{}", err);
+                    exit(1);
+                }
+            }
+            _ => {
+                eprintln!("Usage: compare-inputs <file> <file>");
+                exit(1);
+            }
+        },
+        Some("--verify-invariants") => {
+            if !verify_invariants() {
+                exit(1);
+            }
+        }
+        Some(other) => {
+            eprintln!(
+                "Unknown command: {} (expected no arguments, 'list', 'generate', 'compare', 'compare-inputs', '--verify-invariants' or flags)",
+                other
+            );
+            exit(1);
+        }
+    }
+    if let Some(path) = trace {
+        if let Err(err) = trace::save(Path::new(&path)) {
+            eprintln!("Unable to write {}: {}", path, err);
+            exit(1);
+        }
+    }
 }
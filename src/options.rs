@@ -0,0 +1,46 @@
+//! Settings for individual days, given on the command line as `--set name=value` (or `--set name`
+//! to switch something on)
+//! Each day lists the settings it reads in its `parameters`, shown by `list`
+
+use std::sync::Mutex;
+
+/// Every setting from the command line, in the order given
+static SETTINGS: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+
+/// Records a `name=value` setting; a bare `name` is stored as "true"
+pub fn set(setting: &str) {
+    let (name, value) = setting.split_once('=').unwrap_or((setting, "true"));
+    SETTINGS
+        .lock()
+        .unwrap()
+        .push((name.trim().to_string(), value.trim().to_string()));
+}
+
+/// Returns the value given for `name`, if any (the last one wins if it was set more than once)
+pub fn get(name: &str) -> Option<String> {
+    SETTINGS
+        .lock()
+        .unwrap()
+        .iter()
+        .rev()
+        .find(|(setting, _)| setting == name)
+        .map(|(_, value)| value.clone())
+}
+
+/// Returns true if `name` was switched on
+pub fn enabled(name: &str) -> bool {
+    get(name).is_some_and(|value| value != "false")
+}
+
+#[test]
+fn test_settings() {
+    set("test-metric=chebyshev");
+    set("test-metric = euclidean");
+    assert_eq!(get("test-metric"), Some("euclidean".to_string()));
+    assert_eq!(get("test-missing"), None);
+    set("test-render");
+    assert!(enabled("test-render"));
+    assert!(!enabled("test-missing"));
+    set("test-render=false");
+    assert!(!enabled("test-render"));
+}
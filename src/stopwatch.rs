@@ -0,0 +1,47 @@
+//! A lightweight stopwatch for timing the stages of a solve (reading, parsing, solving...)
+//! The runner starts it before each part; solvers call `lap` at the end of each stage they want
+//! timed, and the runner reports the stages afterwards if `--timings` was given
+
+use crate::trace;
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+thread_local! {
+    /// When the current stage started, and the stages finished so far
+    static LAPS: RefCell<(Instant, Vec<(&'static str, Duration)>)> =
+        RefCell::new((Instant::now(), Vec::new()));
+}
+
+/// Starts timing a new part, forgetting any previous stages
+pub fn start() {
+    LAPS.with(|laps| *laps.borrow_mut() = (Instant::now(), Vec::new()));
+}
+
+/// Marks the end of `stage`: the time since the previous lap (or `start`) is recorded against it
+pub fn lap(stage: &'static str) {
+    LAPS.with(|laps| {
+        let (started, stages) = &mut *laps.borrow_mut();
+        stages.push((stage, started.elapsed()));
+        trace::record(stage, Vec::new(), *started);
+        *started = Instant::now();
+    });
+}
+
+/// Stops timing and returns every stage in order
+/// Time after the last lap is reported as "output", as that's where the parts print their answers
+pub fn finish() -> Vec<(&'static str, Duration)> {
+    lap("output");
+    LAPS.with(|laps| laps.borrow_mut().1.split_off(0))
+}
+
+#[test]
+fn test_laps_are_recorded_in_order() {
+    start();
+    lap("parse");
+    std::thread::sleep(Duration::from_millis(5));
+    lap("solve");
+    let stages = finish();
+    let names: Vec<&str> = stages.iter().map(|(name, _)| *name).collect();
+    assert_eq!(names, vec!["parse", "solve", "output"]);
+    assert!(stages[1].1 >= Duration::from_millis(5));
+}
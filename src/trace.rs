@@ -0,0 +1,162 @@
+//! Spans showing where the time goes, saved in Chrome's trace format for chrome://tracing or
+//! https://ui.perfetto.dev (`--trace trace.json`)
+//! Spans are only recorded when built with the `trace` feature; otherwise `span` does nothing, so
+//! solvers can open them unconditionally
+//! The runner opens one for each day and part, every `stopwatch::lap` stage becomes one, and the
+//! heavier simulations open them for their trials and rounds, with counters as arguments
+
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// A finished span
+struct Event {
+    name: &'static str,
+    args: Vec<(&'static str, String)>,
+    /// Which thread it ran on, numbered in the order threads first recorded a span
+    thread: usize,
+    /// Since the first span was opened
+    start: Duration,
+    duration: Duration,
+}
+
+/// Every span finished so far
+static EVENTS: Mutex<Vec<Event>> = Mutex::new(Vec::new());
+
+/// When the trace started; span times are measured from here
+static EPOCH: OnceLock<Instant> = OnceLock::new();
+
+/// Hands out thread numbers
+static THREADS: AtomicUsize = AtomicUsize::new(1);
+
+thread_local! {
+    static THREAD: usize = THREADS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Records that `name` ran from `start` until now
+pub fn record(name: &'static str, args: Vec<(&'static str, String)>, start: Instant) {
+    if !cfg!(feature = "trace") {
+        return;
+    }
+    let epoch = *EPOCH.get_or_init(|| start);
+    let event = Event {
+        name,
+        args,
+        thread: THREAD.with(|thread| *thread),
+        start: start.saturating_duration_since(epoch),
+        duration: start.elapsed(),
+    };
+    EVENTS.lock().unwrap().push(event);
+}
+
+/// Times from now until it's dropped
+pub struct Span {
+    name: &'static str,
+    args: Vec<(&'static str, String)>,
+    /// None if we're not tracing
+    start: Option<Instant>,
+}
+
+/// Opens a span called `name`
+pub fn span(name: &'static str) -> Span {
+    let start = if cfg!(feature = "trace") {
+        let now = Instant::now();
+        EPOCH.get_or_init(|| now);
+        Some(now)
+    } else {
+        None
+    };
+    Span {
+        name,
+        args: Vec::new(),
+        start,
+    }
+}
+
+impl Span {
+    /// Attaches a counter or setting to the span, eg. which round of a battle it is
+    pub fn arg(mut self, key: &'static str, value: impl Display) -> Span {
+        if self.start.is_some() {
+            self.args.push((key, value.to_string()));
+        }
+        self
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        if let Some(start) = self.start {
+            record(self.name, self.args.split_off(0), start);
+        }
+    }
+}
+
+/// Quotes `text` as a JSON string
+fn quote(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for ch in text.chars() {
+        match ch {
+            '"' => quoted += "\\\"",
+            '\\' => quoted += "\\\\",
+            ch if (ch as u32) < 0x20 => quoted += &format!("\\u{:04x}", ch as u32),
+            ch => quoted.push(ch),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Writes every span recorded so far in Chrome's trace format
+fn chrome_trace() -> String {
+    let events: Vec<String> = EVENTS
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|event| {
+            let args: Vec<String> = event
+                .args
+                .iter()
+                .map(|(key, value)| format!("{}:{}", quote(key), quote(value)))
+                .collect();
+            format!(
+                "{{\"name\":{},\"ph\":\"X\",\"pid\":1,\"tid\":{},\"ts\":{},\"dur\":{},\"args\":{{{}}}}}",
+                quote(event.name),
+                event.thread,
+                event.start.as_micros(),
+                event.duration.as_micros(),
+                args.join(",")
+            )
+        })
+        .collect();
+    format!("{{\"traceEvents\":[\n{}\n]}}\n", events.join(",\n"))
+}
+
+/// Saves every span recorded so far to `path`
+pub fn save(path: &Path) -> io::Result<()> {
+    fs::write(path, chrome_trace())
+}
+
+#[test]
+fn test_quote() {
+    assert_eq!(quote("round"), "\"round\"");
+    assert_eq!(quote("a \"b\"\\\n"), "\"a \\\"b\\\"\\\\\\u000a\"");
+}
+
+#[test]
+#[cfg(feature = "trace")]
+fn test_spans() {
+    {
+        let _span = span("test-outer").arg("day", 15);
+        let _inner = span("test-inner");
+    }
+    let trace = chrome_trace();
+    let inner = trace.find("\"name\":\"test-inner\"").unwrap();
+    let outer = trace.find("\"name\":\"test-outer\"").unwrap();
+    // The inner span finishes first
+    assert!(inner < outer);
+    assert!(trace.contains("\"args\":{\"day\":\"15\"}"));
+}
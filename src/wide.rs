@@ -0,0 +1,108 @@
+//! The integer type used by accumulation-heavy solvers (running sums, scores, extrapolated totals)
+//! Puzzle-sized inputs fit in an i64, but stress-sized or adversarial ones may not, so the
+//! `wide-int` feature swaps in an i128 rather than letting the sums silently wrap
+//! To find out whether an input needs it, the `checked-arith` feature makes `add` check every sum,
+//! turning an overflow into an error instead of a plausible looking (but wrong) answer
+//! `checked_add` and `checked_mul` always check, for the few places (like extrapolating far into
+//! the future) where the numbers get big in one go rather than a little at a time
+
+use std::error::Error;
+use std::fmt;
+
+/// A signed integer wide enough for the puzzle's accumulators
+#[cfg(not(feature = "wide-int"))]
+pub type Int = i64;
+
+/// A signed integer wide enough for the puzzle's accumulators
+#[cfg(feature = "wide-int")]
+pub type Int = i128;
+
+/// An accumulator overflowed `Int`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Overflow {
+    /// The day whose solver overflowed
+    pub day: usize,
+    /// The value before the overflow
+    pub value: Int,
+    /// The operation: '+' or '*'
+    pub op: char,
+    /// What we were adding to it, or multiplying it by
+    pub operand: Int,
+}
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Day {}: {} {} {} overflowed (try the wide-int feature)",
+            self.day, self.value, self.op, self.operand
+        )
+    }
+}
+
+impl Error for Overflow {}
+
+/// Adds `adding` to `value` for `day`, whatever the features
+pub fn checked_add(day: usize, value: Int, adding: Int) -> Result<Int, Overflow> {
+    value.checked_add(adding).ok_or(Overflow {
+        day,
+        value,
+        op: '+',
+        operand: adding,
+    })
+}
+
+/// Multiplies `value` by `by` for `day`, whatever the features
+pub fn checked_mul(day: usize, value: Int, by: Int) -> Result<Int, Overflow> {
+    value.checked_mul(by).ok_or(Overflow {
+        day,
+        value,
+        op: '*',
+        operand: by,
+    })
+}
+
+/// Adds `adding` to a running total for `day`
+/// Only checked for overflow with the `checked-arith` feature, as it's on the hot path
+#[cfg(feature = "checked-arith")]
+pub fn add(day: usize, value: Int, adding: Int) -> Result<Int, Overflow> {
+    checked_add(day, value, adding)
+}
+
+/// Adds `adding` to a running total for `day`
+/// Only checked for overflow with the `checked-arith` feature, as it's on the hot path
+#[cfg(not(feature = "checked-arith"))]
+pub fn add(_day: usize, value: Int, adding: Int) -> Result<Int, Overflow> {
+    Ok(value + adding)
+}
+
+#[test]
+fn test_add() {
+    assert_eq!(add(1, 2, -5), Ok(-3));
+    #[cfg(feature = "checked-arith")]
+    assert_eq!(
+        add(9, Int::MAX - 1, 2),
+        Err(Overflow {
+            day: 9,
+            value: Int::MAX - 1,
+            op: '+',
+            operand: 2
+        })
+    );
+}
+
+#[test]
+fn test_checked() {
+    assert_eq!(checked_add(12, Int::MAX, -1), Ok(Int::MAX - 1));
+    assert!(checked_add(12, Int::MAX, 1).is_err());
+    assert_eq!(checked_mul(12, -4, 5), Ok(-20));
+    assert_eq!(
+        checked_mul(12, Int::MAX / 2, 3),
+        Err(Overflow {
+            day: 12,
+            value: Int::MAX / 2,
+            op: '*',
+            operand: 3
+        })
+    );
+}